@@ -3,7 +3,9 @@ use core::arch::asm;
 use cortex_a::registers::SPSR_EL1;
 
 use crate::arch::instructions;
+use crate::config::{USER_ASPACE_BASE, USER_ASPACE_SIZE};
 use crate::mm::{PhysAddr, VirtAddr};
+use crate::uintr::UintrContext;
 
 #[repr(C)]
 #[derive(Debug, Default, Clone, Copy)]
@@ -48,6 +50,59 @@ impl TrapFrame {
         tf
     }
 
+    /// Arguments of the syscall, in the registers of the Linux ABI.
+    pub const fn syscall_args(&self) -> [usize; 6] {
+        [
+            self.r[0] as _,
+            self.r[1] as _,
+            self.r[2] as _,
+            self.r[3] as _,
+            self.r[4] as _,
+            self.r[5] as _,
+        ]
+    }
+
+    pub fn is_user(&self) -> bool {
+        self.spsr & 0b1111 == 0 // SPSR_EL1.M == EL0t
+    }
+
+    pub const fn user_sp(&self) -> usize {
+        self.usp as _
+    }
+
+    /// Returns the value of the syscall return register.
+    pub const fn ret_value(&self) -> usize {
+        self.r[0] as _
+    }
+
+    /// Makes the return to user mode continue at `entry` with the stack
+    /// pointer `sp`, passing `arg0` as the first argument.
+    pub fn redirect_user(&mut self, entry: usize, sp: usize, arg0: usize) {
+        self.elr = entry as _;
+        self.usp = sp as _;
+        self.r[0] = arg0 as _;
+    }
+
+    /// Restores a user context saved in user memory, which can not be
+    /// trusted: the privileged fields are always reset to the user ones.
+    ///
+    /// Fails if ELR or SP is not a user address.
+    pub fn restore_user(&mut self, saved: &Self) -> bool {
+        const NZCV_MASK: u64 = 0xf000_0000;
+        let user_aspace = USER_ASPACE_BASE..USER_ASPACE_BASE + USER_ASPACE_SIZE;
+        if !user_aspace.contains(&(saved.elr as usize))
+            || !user_aspace.contains(&(saved.usp as usize))
+        {
+            return false;
+        }
+        let user_spsr = Self::new_user(VirtAddr::new(0), VirtAddr::new(0), 0).spsr;
+        *self = Self {
+            spsr: (saved.spsr & NZCV_MASK) | user_spsr,
+            ..*saved
+        };
+        true
+    }
+
     pub unsafe fn exec(&self, kstack_top: VirtAddr) -> ! {
         info!(
             "user task start: entry={:#x}, ustack={:#x}, kstack={:#x}",
@@ -106,6 +161,9 @@ pub struct TaskContext {
     pub r29: u64,
     pub lr: u64, // r30
     pub ttbr0_el1: u64,
+    /// There is no hardware support of user interrupts, so the state is only
+    /// kept but never switched.
+    pub uintr: UintrContext,
 }
 
 impl TaskContext {
@@ -129,6 +187,11 @@ impl TaskContext {
         };
     }
 
+    /// Updates the user interrupt state of the running task.
+    pub fn set_uintr(&mut self, uintr: UintrContext) {
+        self.uintr = uintr;
+    }
+
     pub fn switch_to(&mut self, next_ctx: &Self) {
         unsafe {
             instructions::set_user_page_table_root(next_ctx.ttbr0_el1 as usize);
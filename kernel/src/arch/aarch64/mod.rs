@@ -10,6 +10,11 @@ pub use self::context::{TaskContext, TrapFrame};
 pub use self::page_table::{PageTable, PageTableEntry};
 pub use self::percpu::ArchPerCpu;
 
+/// There is no hardware support of user interrupts.
+pub fn uintr_backend() -> Option<&'static dyn crate::uintr::UintrBackend> {
+    None
+}
+
 pub fn init() {
     trap::init();
 }
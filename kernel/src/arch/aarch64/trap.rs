@@ -5,7 +5,7 @@ use tock_registers::interfaces::{Readable, Writeable};
 
 use super::TrapFrame;
 use crate::drivers::interrupt::{handle_irq, IrqHandlerResult};
-use crate::{syscall::syscall, task::CurrentTask};
+use crate::{syscall::syscall, task::CurrentTask, uintr};
 
 global_asm!(include_str!("trap.S"));
 
@@ -61,7 +61,7 @@ fn handle_sync_exception(tf: &mut TrapFrame) {
             CurrentTask::get().exit(-1);
         }
         Some(ESR_EL1::EC::Value::SVC64) => {
-            tf.r[0] = syscall(tf, tf.r[8] as _, tf.r[0] as _, tf.r[1] as _, tf.r[2] as _) as u64
+            tf.r[0] = syscall(tf, tf.r[8] as _, tf.syscall_args()) as u64
         }
         Some(ESR_EL1::EC::Value::DataAbortLowerEL)
         | Some(ESR_EL1::EC::Value::InstrAbortLowerEL) => {
@@ -94,11 +94,17 @@ fn handle_sync_exception(tf: &mut TrapFrame) {
             );
         }
     }
+    if tf.is_user() {
+        uintr::deliver_pending(tf);
+    }
 }
 
 #[no_mangle]
-fn handle_irq_exception(_tf: &mut TrapFrame) {
+fn handle_irq_exception(tf: &mut TrapFrame) {
     if handle_irq(0) == IrqHandlerResult::Reschedule {
         CurrentTask::get().yield_now();
     }
+    if tf.is_user() {
+        uintr::deliver_pending(tf);
+    }
 }
@@ -4,8 +4,10 @@ use x86_64::registers::rflags::RFlags;
 
 use super::gdt::{UCODE64_SELECTOR, UDATA_SELECTOR};
 use crate::arch::instructions;
+use crate::config::{USER_ASPACE_BASE, USER_ASPACE_SIZE};
 use crate::mm::{PhysAddr, VirtAddr};
 use crate::percpu::PerCpu;
+use crate::uintr::UintrContext;
 
 #[repr(C)]
 #[derive(Debug, Default, Clone, Copy)]
@@ -72,10 +74,95 @@ impl TrapFrame {
         tf
     }
 
+    /// Arguments of the syscall, in the registers of the Linux ABI.
+    pub const fn syscall_args(&self) -> [usize; 6] {
+        [
+            self.rdi as _,
+            self.rsi as _,
+            self.rdx as _,
+            self.r10 as _,
+            self.r8 as _,
+            self.r9 as _,
+        ]
+    }
+
     pub fn is_user(&self) -> bool {
         self.cs & 0b11 == 3
     }
 
+    pub const fn user_sp(&self) -> usize {
+        self.user_rsp as _
+    }
+
+    /// Returns the value of the general purpose register numbered `reg` in
+    /// instruction encodings.
+    pub fn gpr(&self, reg: usize) -> u64 {
+        match reg {
+            0 => self.rax,
+            1 => self.rcx,
+            2 => self.rdx,
+            3 => self.rbx,
+            4 => self.user_rsp,
+            5 => self.rbp,
+            6 => self.rsi,
+            7 => self.rdi,
+            8 => self.r8,
+            9 => self.r9,
+            10 => self.r10,
+            11 => self.r11,
+            12 => self.r12,
+            13 => self.r13,
+            14 => self.r14,
+            15 => self.r15,
+            _ => panic!("invalid register number: {}", reg),
+        }
+    }
+
+    /// Returns the value of the syscall return register.
+    pub const fn ret_value(&self) -> usize {
+        self.rax as _
+    }
+
+    /// Makes the return to user mode continue at `entry` with the stack
+    /// pointer `sp`, passing `arg0` as the first argument.
+    pub fn redirect_user(&mut self, entry: usize, sp: usize, arg0: usize) {
+        self.rip = entry as _;
+        self.user_rsp = sp as _;
+        self.rdi = arg0 as _;
+    }
+
+    /// Restores a user context saved in user memory, which can not be
+    /// trusted: the privileged fields are always reset to the user ones.
+    ///
+    /// Fails if RIP or RSP is not a user address, which would fault in the
+    /// kernel on the way back to user mode.
+    pub fn restore_user(&mut self, saved: &Self) -> bool {
+        let user_aspace = USER_ASPACE_BASE..USER_ASPACE_BASE + USER_ASPACE_SIZE;
+        if !user_aspace.contains(&(saved.rip as usize))
+            || !user_aspace.contains(&(saved.user_rsp as usize))
+        {
+            return false;
+        }
+        const USER_RFLAGS: RFlags = RFlags::from_bits_truncate(
+            RFlags::CARRY_FLAG.bits()
+                | RFlags::PARITY_FLAG.bits()
+                | RFlags::AUXILIARY_CARRY_FLAG.bits()
+                | RFlags::ZERO_FLAG.bits()
+                | RFlags::SIGN_FLAG.bits()
+                | RFlags::DIRECTION_FLAG.bits()
+                | RFlags::OVERFLOW_FLAG.bits(),
+        );
+        *self = Self {
+            vector: self.vector,
+            error_code: self.error_code,
+            cs: UCODE64_SELECTOR.0 as _,
+            rflags: (saved.rflags & USER_RFLAGS.bits()) | RFlags::INTERRUPT_FLAG.bits(),
+            user_ss: UDATA_SELECTOR.0 as _,
+            ..*saved
+        };
+        true
+    }
+
     pub unsafe fn exec(&self, kstack_top: VirtAddr) -> ! {
         info!(
             "user task start: entry={:#x}, ustack={:#x}, kstack={:#x}",
@@ -132,6 +219,7 @@ pub struct TaskContext {
     pub rsp: u64,
     pub fs_base: u64,
     pub cr3: u64,
+    pub uintr: UintrContext,
 }
 
 impl TaskContext {
@@ -161,6 +249,15 @@ impl TaskContext {
         self.cr3 = page_table_root.as_usize() as u64;
     }
 
+    /// Updates the user interrupt state of the running task.
+    pub fn set_uintr(&mut self, uintr: UintrContext) {
+        self.uintr = UintrContext {
+            uirr: self.uintr.uirr,
+            ..uintr
+        };
+        super::uintr::load(&self.uintr);
+    }
+
     pub fn switch_to(&mut self, next_ctx: &Self) {
         unsafe {
             PerCpu::current()
@@ -169,6 +266,7 @@ impl TaskContext {
                 .set_kernel_stack_top(next_ctx.kstack_top);
             instructions::set_user_page_table_root(next_ctx.cr3 as usize);
             // TODO: swtich fs_base
+            super::uintr::switch(&mut self.uintr, &next_ctx.uintr);
             context_switch(&mut self.rsp, &next_ctx.rsp)
         }
     }
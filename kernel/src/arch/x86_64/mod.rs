@@ -5,6 +5,7 @@ mod page_table;
 mod percpu;
 mod syscall;
 mod trap;
+mod uintr;
 
 pub mod config;
 pub mod instructions;
@@ -12,6 +13,7 @@ pub mod instructions;
 pub use self::context::{TaskContext, TrapFrame};
 pub use self::page_table::{PageTable, PageTableEntry};
 pub use self::percpu::ArchPerCpu;
+pub use self::uintr::uintr_backend;
 
 pub fn init() {
     idt::init();
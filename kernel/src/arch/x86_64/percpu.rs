@@ -37,6 +37,7 @@ impl ArchPerCpu {
         self.gdt.load();
         self.gdt.load_tss(TSS_SELECTOR);
         super::syscall::init_percpu();
+        super::uintr::init_percpu();
     }
 
     pub fn kernel_stack_top(&self) -> VirtAddr {
@@ -7,7 +7,7 @@ use x86_64::registers::rflags::RFlags;
 use super::context::TrapFrame;
 use super::gdt::{KCODE64_SELECTOR, KDATA_SELECTOR, UCODE64_SELECTOR, UDATA_SELECTOR};
 use super::percpu::{PERCPU_KERNEL_RSP_OFFSET, PERCPU_USER_RSP_OFFSET};
-use crate::syscall::syscall;
+use crate::{syscall::syscall, uintr};
 
 global_asm!(
     include_str!("syscall.S"),
@@ -15,9 +15,22 @@ global_asm!(
     saved_kernel_rsp_offset = const PERCPU_KERNEL_RSP_OFFSET,
 );
 
+/// Returns `true` if the syscall must return by `iretq` instead of `sysretq`.
 #[no_mangle]
-fn x86_syscall_handler(tf: &mut TrapFrame) {
-    tf.rax = syscall(tf, tf.rax as _, tf.rdi as _, tf.rsi as _, tf.rdx as _) as u64;
+fn x86_syscall_handler(tf: &mut TrapFrame) -> bool {
+    tf.rax = syscall(tf, tf.rax as _, tf.syscall_args()) as u64;
+    uintr::deliver_pending(tf);
+    // `sysretq` restores RIP and RFLAGS from RCX and R11, which is only correct
+    // if the trap frame is not replaced (e.g., by `uiret`) during the syscall.
+    // It also raises #GP in the kernel if RCX is not canonical.
+    if tf.rcx == tf.rip && tf.r11 == tf.rflags && VirtAddr::try_new(tf.rcx).is_ok() {
+        false
+    } else {
+        // cs, user_ss are not pushed into TrapFrame in syscall_entry
+        tf.cs = UCODE64_SELECTOR.0 as _;
+        tf.user_ss = UDATA_SELECTOR.0 as _;
+        true
+    }
 }
 
 pub fn init_percpu() {
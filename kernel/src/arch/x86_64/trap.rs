@@ -4,7 +4,7 @@ use x86::{controlregs::cr2, irq::*};
 
 use super::context::TrapFrame;
 use crate::drivers::interrupt::{handle_irq, IrqHandlerResult};
-use crate::{syscall::syscall, task::CurrentTask};
+use crate::{syscall::syscall, task::CurrentTask, uintr};
 
 global_asm!(include_str!("trap.S"));
 
@@ -35,6 +35,7 @@ fn x86_trap_handler(tf: &mut TrapFrame) {
                 );
             }
         }
+        INVALID_OPCODE_VECTOR if tf.is_user() && super::uintr::emulate_insn(tf) => {}
         GENERAL_PROTECTION_FAULT_VECTOR => {
             warn!(
                 "General Protection Exception @ {:#x}, error_code = {:#x}, kernel killed it.",
@@ -42,9 +43,7 @@ fn x86_trap_handler(tf: &mut TrapFrame) {
             );
             CurrentTask::get().exit(-1);
         }
-        SYSCALL_VECTOR => {
-            tf.rax = syscall(tf, tf.rax as _, tf.rdi as _, tf.rsi as _, tf.rdx as _) as u64
-        }
+        SYSCALL_VECTOR => tf.rax = syscall(tf, tf.rax as _, tf.syscall_args()) as u64,
         IRQ_VECTOR_START..=IRQ_VECTOR_END => {
             if handle_irq(tf.vector as usize) == IrqHandlerResult::Reschedule {
                 CurrentTask::get().yield_now();
@@ -57,4 +56,7 @@ fn x86_trap_handler(tf: &mut TrapFrame) {
             );
         }
     }
+    if tf.is_user() {
+        uintr::deliver_pending(tf);
+    }
 }
@@ -0,0 +1,274 @@
+//! User interrupt (UINTR) support of the CPU.
+
+use core::arch::asm;
+use core::mem::size_of;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use raw_cpuid::{cpuid, CpuId};
+use x86_64::registers::control::Cr4;
+use x86_64::registers::model_specific::Msr;
+
+use super::context::TrapFrame;
+use crate::drivers::interrupt::IrqHandlerResult;
+use crate::drivers::interrupt::{is_x2apic, local_apic_id, register_handler, send_ipi};
+use crate::mm::{MemFlags, UserInPtr};
+use crate::task::CurrentTask;
+use crate::uintr::{self, UintrBackend, UintrContext, Upid};
+
+const IA32_UINTR_RR: u32 = 0x985;
+const IA32_UINTR_HANDLER: u32 = 0x986;
+const IA32_UINTR_STACKADJUST: u32 = 0x987;
+const IA32_UINTR_MISC: u32 = 0x988;
+const IA32_UINTR_PD: u32 = 0x989;
+const IA32_UINTR_TT: u32 = 0x98a;
+
+/// CR4.UINTR, enables user interrupts.
+const CR4_UINTR: u64 = 1 << 25;
+/// CPUID.(EAX=07H,ECX=0):EDX.UINTR.
+const CPUID_EDX_UINTR: u32 = 1 << 5;
+/// The valid bit of IA32_UINTR_TT.
+const UITT_VALID: u64 = 1;
+
+/// The ordinary interrupt vector used for user interrupt notifications.
+const UINTR_NOTIFICATION_VECTOR: u8 = 0xec;
+
+static UINTR_SUPPORTED: AtomicBool = AtomicBool::new(false);
+
+/// Encodes `apic_id` as the NDST field of a UPID. The CPU sends notifications
+/// to the 32-bit ID in x2APIC mode, but to the 8-bit ID in bits 15:8 of the
+/// field in xAPIC mode, which are bits 47:40 of the notification control word.
+fn apic_id_to_ndst(apic_id: u32) -> u32 {
+    if is_x2apic() {
+        apic_id
+    } else {
+        (apic_id & 0xff) << 8
+    }
+}
+
+/// Decodes the NDST field of a UPID encoded by `apic_id_to_ndst`.
+fn ndst_to_apic_id(ndst: u32) -> u32 {
+    if is_x2apic() {
+        ndst
+    } else {
+        (ndst >> 8) & 0xff
+    }
+}
+
+/// Whether the CPU supports user interrupts.
+pub fn has_uintr() -> bool {
+    UINTR_SUPPORTED.load(Ordering::Relaxed)
+}
+
+pub(super) fn init_percpu() {
+    let supported = CpuId::new().get_extended_feature_info().is_some()
+        && cpuid!(0x7, 0).edx & CPUID_EDX_UINTR != 0;
+    UINTR_SUPPORTED.store(supported, Ordering::Relaxed);
+    if supported {
+        unsafe { Cr4::write_raw(Cr4::read_raw() | CR4_UINTR) };
+    }
+}
+
+fn upid_of(ctx: &UintrContext) -> Option<&Upid> {
+    if ctx.upid_addr != 0 {
+        Some(unsafe { &*(ctx.upid_addr as *const Upid) })
+    } else {
+        None
+    }
+}
+
+/// Loads the user interrupt state of `ctx` into the MSRs, except the
+/// pending requests in IA32_UINTR_RR.
+pub(super) fn load(ctx: &UintrContext) {
+    if !has_uintr() {
+        return;
+    }
+    let (tt, uittsz) = if ctx.uitt_addr != 0 && ctx.uitt_size != 0 {
+        (ctx.uitt_addr as u64 | UITT_VALID, ctx.uitt_size as u64 - 1)
+    } else {
+        (0, 0)
+    };
+    let uinv = if ctx.upid_addr != 0 {
+        UINTR_NOTIFICATION_VECTOR as u64
+    } else {
+        0
+    };
+    unsafe {
+        Msr::new(IA32_UINTR_HANDLER).write(ctx.handler as u64);
+        Msr::new(IA32_UINTR_STACKADJUST).write(ctx.stack_adjust as u64);
+        Msr::new(IA32_UINTR_MISC).write(uinv << 32 | uittsz);
+        Msr::new(IA32_UINTR_PD).write(ctx.upid_addr as u64);
+        Msr::new(IA32_UINTR_TT).write(tt);
+    }
+}
+
+/// Saves the user interrupt state of the previous task, and loads the one of
+/// the next task.
+///
+/// The UPID of a receiver suppresses notifications while it is switched out,
+/// so that the posted interrupts stay in the PIR until it runs again.
+pub(super) fn switch(prev: &mut UintrContext, next: &UintrContext) {
+    if !has_uintr() {
+        return;
+    }
+    unsafe {
+        prev.uirr = Msr::new(IA32_UINTR_RR).read();
+        if let Some(upid) = upid_of(prev) {
+            upid.suspend();
+        }
+        load(next);
+        Msr::new(IA32_UINTR_RR).write(next.uirr);
+        if let Some(upid) = upid_of(next) {
+            let apic_id = local_apic_id();
+            if upid.resume(apic_id_to_ndst(apic_id)) {
+                send_ipi(UINTR_NOTIFICATION_VECTOR as usize, apic_id);
+            }
+        }
+    }
+}
+
+/// User interrupts handled by the CPU, with `SENDUIPI` and `UIRET` executed
+/// in user mode directly.
+struct HardwareUintr;
+
+impl UintrBackend for HardwareUintr {
+    fn name(&self) -> &'static str {
+        "hardware"
+    }
+
+    fn init_upid(&self, upid: &Upid) {
+        upid.set_notification_vector(UINTR_NOTIFICATION_VECTOR);
+    }
+
+    fn post(&self, upid: &Upid, vector: usize) {
+        if upid.post(vector) {
+            send_ipi(
+                UINTR_NOTIFICATION_VECTOR as usize,
+                ndst_to_apic_id(upid.notification_dest()),
+            );
+        }
+    }
+
+    fn has_pending(&self, upid: &Upid) -> bool {
+        // also the ones that have been recognized by the CPU
+        upid.has_pending() || unsafe { Msr::new(IA32_UINTR_RR).read() } != 0
+    }
+
+    fn deliver_pending(&self, _tf: &mut TrapFrame) {
+        // delivered by the CPU on the return to user mode
+    }
+
+    /// Pops the frame pushed by the CPU, as `UIRET` does. RCX and R11 have
+    /// been clobbered by `syscall` though, so handlers should better execute
+    /// `UIRET` directly.
+    fn uiret(&self, tf: &mut TrapFrame) -> bool {
+        #[repr(C)]
+        struct UintrFrame {
+            rip: u64,
+            rflags: u64,
+            rsp: u64,
+        }
+        let sp = tf.user_sp();
+        if sp % 8 != 0
+            || !CurrentTask::get().check_user_range(sp, size_of::<UintrFrame>(), MemFlags::READ)
+        {
+            return false;
+        }
+        let frame = UserInPtr::<UintrFrame>::from(sp).read();
+        let saved = TrapFrame {
+            rip: frame.rip,
+            rflags: frame.rflags,
+            user_rsp: frame.rsp,
+            ..*tf
+        };
+        if !tf.restore_user(&saved) {
+            return false;
+        }
+        unsafe { asm!("stui") };
+        true
+    }
+
+    fn stui(&self) {
+        unsafe { asm!("stui") };
+    }
+
+    fn clui(&self) {
+        unsafe { asm!("clui") };
+    }
+}
+
+/// Returns the hardware backend of user interrupts if the CPU supports them,
+/// after setting up the handler of notifications received in kernel mode.
+pub fn uintr_backend() -> Option<&'static dyn UintrBackend> {
+    if has_uintr() {
+        register_handler(UINTR_NOTIFICATION_VECTOR as usize, |_| {
+            IrqHandlerResult::NoReschedule
+        });
+        Some(&HardwareUintr)
+    } else {
+        None
+    }
+}
+
+/// Emulates `UIRET`, `STUI`, `CLUI` and `SENDUIPI` executed in user mode on CPUs
+/// without user interrupt support, which raise #UD. Returns `false` if the
+/// instruction at `tf.rip` is none of them.
+pub(super) fn emulate_insn(tf: &mut TrapFrame) -> bool {
+    const UIRET: [u8; 4] = [0xf3, 0x0f, 0x01, 0xec];
+    const STUI: [u8; 4] = [0xf3, 0x0f, 0x01, 0xef];
+    const CLUI: [u8; 4] = [0xf3, 0x0f, 0x01, 0xee];
+    let curr = CurrentTask::get();
+    let rip = tf.rip as usize;
+    if has_uintr() || !curr.check_user_range(rip, 4, MemFlags::READ) {
+        return false;
+    }
+    let insn: [u8; 4] = UserInPtr::<u8>::from(rip).read_array(4);
+    if insn == UIRET {
+        if !uintr::uiret(tf) {
+            warn!(
+                "Invalid user interrupt frame @ {:#x}, kernel killed it.",
+                rip
+            );
+            curr.exit(-1);
+        }
+        return true;
+    }
+    if insn == STUI || insn == CLUI {
+        if insn == STUI {
+            uintr::stui();
+        } else {
+            uintr::clui();
+        }
+        tf.rip += insn.len() as u64;
+        return true;
+    }
+
+    // SENDUIPI reg: F3 [REX] 0F C7 /6, with a register operand
+    let (rex, len) = match insn[1] {
+        rex @ 0x40..=0x4f => (rex, 5),
+        _ => (0, 4),
+    };
+    if insn[0] != 0xf3 || insn[len - 3..len - 1] != [0x0f, 0xc7] {
+        return false;
+    }
+    let modrm = if len == 4 {
+        insn[3]
+    } else if curr.check_user_range(rip + 4, 1, MemFlags::READ) {
+        UserInPtr::<u8>::from(rip + 4).read()
+    } else {
+        return false;
+    };
+    if modrm >> 6 != 0b11 || (modrm >> 3) & 0b111 != 6 {
+        return false;
+    }
+    let reg = (modrm & 0b111) as usize | ((rex as usize & 1) << 3);
+    let index = tf.gpr(reg) as usize;
+    if !curr.uintr().lock().send(index) {
+        warn!(
+            "Invalid UITT index {} @ {:#x}, kernel killed it.",
+            index, rip
+        );
+        curr.exit(-1);
+    }
+    tf.rip += len as u64;
+    true
+}
@@ -2,28 +2,92 @@
 
 #![allow(dead_code)]
 
+use x2apic::ioapic::IoApic;
 use x2apic::lapic::{xapic_base, LocalApic, LocalApicBuilder, TimerDivide, TimerMode};
+use x86_64::instructions::port::PortWriteOnly;
+use x86_64::registers::model_specific::Msr;
 
 use super::IrqHandlerResult;
 use crate::config::TICKS_PER_SEC;
 use crate::mm::PhysAddr;
 use crate::percpu::PerCpuData;
-use crate::sync::LazyInit;
+use crate::sync::{LazyInit, Mutex};
 
 const APIC_TIMER_VECTOR: usize = 0xf0;
 const APIC_SPURIOUS_VECTOR: usize = 0xf1;
 const APIC_ERROR_VECTOR: usize = 0xf2;
 
+const IOAPIC_BASE: PhysAddr = PhysAddr::new(0xfec0_0000);
+/// External IRQs are mapped to the vectors right after the exceptions.
+const IOAPIC_IRQ_VECTOR_BASE: usize = 0x20;
+const IOAPIC_IRQ_COUNT: usize = 24;
+
 pub const IRQ_COUNT: usize = 256;
 
+const IA32_APIC_BASE: u32 = 0x1b;
+const IA32_X2APIC_APICID: u32 = 0x802;
+/// IA32_APIC_BASE.EXTD, set in x2APIC mode.
+const APIC_BASE_EXTD: u64 = 1 << 10;
+/// Offset of the ID register in the xAPIC MMIO page.
+const XAPIC_ID_OFFSET: usize = 0x20;
+
 static LOCAL_APIC: LazyInit<PerCpuData<LocalApic>> = LazyInit::new();
 
+/// Serializes the accesses to the IO APIC, which are done through a pair of
+/// index and data registers.
+static IO_APIC_LOCK: Mutex<()> = Mutex::new(());
+
 fn lapic_eoi() {
     unsafe { LOCAL_APIC.as_mut().end_of_interrupt() };
 }
 
-pub fn set_enable(_vector: usize, _enable: bool) {
-    // TODO: implement IOAPIC
+fn io_apic() -> IoApic {
+    unsafe { IoApic::new(IOAPIC_BASE.into_kvaddr().as_usize() as u64) }
+}
+
+pub fn set_enable(vector: usize, enable: bool) {
+    match vector.checked_sub(IOAPIC_IRQ_VECTOR_BASE) {
+        Some(irq) if irq < IOAPIC_IRQ_COUNT => {
+            let _lock = IO_APIC_LOCK.lock();
+            unsafe {
+                if enable {
+                    io_apic().enable_irq(irq as u8);
+                } else {
+                    io_apic().disable_irq(irq as u8);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns the interrupt vector of the external `irq` of the IO APIC.
+pub fn irq_to_vector(irq: usize) -> Option<usize> {
+    (irq < IOAPIC_IRQ_COUNT).then(|| IOAPIC_IRQ_VECTOR_BASE + irq)
+}
+
+/// Whether the local APIC is in x2APIC mode. `LocalApic::enable` picks it if
+/// the CPU supports it, and the xAPIC mode otherwise.
+pub fn is_x2apic() -> bool {
+    unsafe { Msr::new(IA32_APIC_BASE).read() & APIC_BASE_EXTD != 0 }
+}
+
+/// Returns the APIC ID of the current CPU.
+///
+/// It is read from the registers directly, as the xAPIC ID register holds the
+/// 8-bit ID in bits 31:24, while the x2APIC one holds the 32-bit ID as is.
+pub fn local_apic_id() -> u32 {
+    if is_x2apic() {
+        unsafe { Msr::new(IA32_X2APIC_APICID).read() as u32 }
+    } else {
+        let base = PhysAddr::new(unsafe { xapic_base() } as usize).into_kvaddr();
+        let id_reg = (base.as_usize() + XAPIC_ID_OFFSET) as *const u32;
+        unsafe { id_reg.read_volatile() >> 24 }
+    }
+}
+
+pub fn send_ipi(vector: usize, dest: u32) {
+    unsafe { LOCAL_APIC.as_mut().send_ipi(vector as u8, dest) };
 }
 
 pub fn handle_irq(vector: usize) -> IrqHandlerResult {
@@ -45,7 +109,20 @@ pub fn init() {
         .unwrap();
     unsafe { lapic.enable() };
     LOCAL_APIC.init_by(PerCpuData::new(lapic));
-    super::register_handler(APIC_TIMER_VECTOR, || IrqHandlerResult::Reschedule);
+
+    unsafe {
+        // mask all interrupts of the legacy 8259 PICs
+        PortWriteOnly::<u8>::new(0x21).write(0xff);
+        PortWriteOnly::<u8>::new(0xa1).write(0xff);
+        // all external IRQs are masked until enabled by `set_enable`
+        io_apic().init(IOAPIC_IRQ_VECTOR_BASE as u8);
+    }
+    super::register_handler(APIC_TIMER_VECTOR, |_| {
+        crate::task::wake_sleeping_tasks();
+        crate::uintr::wake_waiting_receivers();
+        crate::uintr::expire_timers();
+        IrqHandlerResult::Reschedule
+    });
 }
 
 pub fn init_local_apic_ap() {
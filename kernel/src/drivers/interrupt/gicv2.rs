@@ -199,6 +199,11 @@ pub fn set_enable(vector: usize, enable: bool) {
     GIC.set_enable(vector, enable);
 }
 
+/// Returns the interrupt vector of the device `irq`, which must be an SPI.
+pub fn irq_to_vector(irq: usize) -> Option<usize> {
+    (SPI_BASE..GIC.max_irqs).contains(&irq).then(|| irq)
+}
+
 pub fn handle_irq(_vector: usize) -> IrqHandlerResult {
     if let Some(vector) = GIC.pending_irq() {
         let res = super::HANDLERS.handle(vector);
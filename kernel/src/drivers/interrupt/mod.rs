@@ -2,14 +2,14 @@ cfg_if! {
     if #[cfg(target_arch = "x86_64")] {
         mod apic;
         use apic as imp;
-        pub use apic::init_local_apic_ap;
+        pub use apic::{init_local_apic_ap, is_x2apic, local_apic_id, send_ipi};
     } else if #[cfg(target_arch = "aarch64")] {
         mod gicv2;
         use gicv2 as imp;
     }
 }
 
-pub use self::imp::{handle_irq, init, set_enable, IRQ_COUNT};
+pub use self::imp::{handle_irq, init, irq_to_vector, set_enable, IRQ_COUNT};
 
 use core::cell::UnsafeCell;
 
@@ -19,7 +19,8 @@ pub enum IrqHandlerResult {
     NoReschedule,
 }
 
-pub type IrqHandler = fn() -> IrqHandlerResult;
+/// Handler of an interrupt, which is called with its vector.
+pub type IrqHandler = fn(usize) -> IrqHandlerResult;
 
 struct IrqHandlerTable<const IRQ_COUNT: usize> {
     handlers: [UnsafeCell<Option<IrqHandler>>; IRQ_COUNT],
@@ -40,10 +41,18 @@ impl<const IRQ_COUNT: usize> IrqHandlerTable<IRQ_COUNT> {
         unsafe { *self.handlers[vector].get() = Some(handler) };
     }
 
+    pub fn unregister_handler(&self, vector: usize) {
+        unsafe { *self.handlers[vector].get() = None };
+    }
+
+    pub fn has_handler(&self, vector: usize) -> bool {
+        unsafe { (*self.handlers[vector].get()).is_some() }
+    }
+
     pub fn handle(&self, vector: usize) -> IrqHandlerResult {
         trace!("IRQ {}", vector);
         if let Some(handler) = unsafe { &*self.handlers[vector].get() } {
-            handler()
+            handler(vector)
         } else {
             IrqHandlerResult::NoReschedule
         }
@@ -55,3 +64,11 @@ static HANDLERS: IrqHandlerTable<IRQ_COUNT> = IrqHandlerTable::new();
 pub fn register_handler(vector: usize, handler: IrqHandler) {
     HANDLERS.register_handler(vector, handler)
 }
+
+pub fn unregister_handler(vector: usize) {
+    HANDLERS.unregister_handler(vector)
+}
+
+pub fn has_handler(vector: usize) -> bool {
+    HANDLERS.has_handler(vector)
+}
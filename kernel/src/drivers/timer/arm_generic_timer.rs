@@ -24,8 +24,10 @@ pub fn init() {
     CLOCK_FREQ.init_by(CNTFRQ_EL0.get());
     CNTP_CTL_EL0.write(CNTP_CTL_EL0::ENABLE::SET);
     set_next_trigger();
-    interrupt::register_handler(PHYS_TIMER_IRQ_NUM, || {
+    interrupt::register_handler(PHYS_TIMER_IRQ_NUM, |_| {
         set_next_trigger();
+        crate::task::wake_sleeping_tasks();
+        crate::uintr::expire_timers();
         IrqHandlerResult::Reschedule
     });
     interrupt::set_enable(PHYS_TIMER_IRQ_NUM, true);
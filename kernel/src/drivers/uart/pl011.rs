@@ -89,6 +89,6 @@ pub fn init_early() {}
 
 pub fn init() {
     UART.lock().init();
-    // UART interrupts are not supported currently
+    // UART interrupts are disabled until forwarded to user space
     crate::drivers::interrupt::set_enable(UART_IRQ_NUM, false);
 }
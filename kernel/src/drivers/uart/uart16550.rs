@@ -64,8 +64,9 @@ impl Uart16550 {
             // and enable auxilliary output #2 (used as interrupt line for CPU)
             self.modem_ctrl.write(0x0B);
 
-            // Enable interrupts
-            self.int_en.write(0x00);
+            // Enable received data available interrupts, which are masked
+            // in the IO APIC until forwarded to user space
+            self.int_en.write(0x01);
         }
     }
 
@@ -25,6 +25,7 @@ mod platform;
 mod sync;
 mod syscall;
 mod task;
+mod uintr;
 mod utils;
 
 #[cfg(not(test))]
@@ -81,6 +82,7 @@ pub fn rust_main() -> ! {
 
     mm::init();
     drivers::init();
+    uintr::init();
 
     task::init();
     loader::list_apps();
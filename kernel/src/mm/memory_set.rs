@@ -1,12 +1,12 @@
 use alloc::collections::btree_map::{BTreeMap, Entry};
-use alloc::vec::Vec;
 use core::fmt;
 
 use super::address::{align_down, is_aligned, phys_to_virt, virt_to_phys};
+use super::shared_memory::{detach_shm_seg, dup_shm_seg, ShmFrames};
 use super::{MemFlags, PhysFrame, PAGE_SIZE};
 use crate::arch::{instructions, PageTable};
 use crate::config::{KERNEL_ASPACE_BASE, KERNEL_ASPACE_SIZE, USER_STACK_BASE, USER_STACK_SIZE};
-use crate::config::{MMIO_REGIONS, PHYS_MEMORY_END};
+use crate::config::{MMIO_REGIONS, PHYS_MEMORY_END, USER_ASPACE_BASE, USER_ASPACE_SIZE};
 use crate::mm::{PhysAddr, VirtAddr};
 use crate::sync::LazyInit;
 
@@ -29,7 +29,12 @@ static KERNEL_ASPACE: LazyInit<MemorySet> = LazyInit::new();
 enum Mapper {
     Offset(usize),
     Framed(BTreeMap<VirtAddr, PhysFrame>),
-    Shared(BTreeMap<usize, usize>),
+    /// Frames of the System V segment `shmid`, or of a POSIX shared memory
+    /// object if it is `None`.
+    Shared {
+        shmid: Option<usize>,
+        frames: ShmFrames,
+    },
 }
 
 pub struct MapArea {
@@ -74,17 +79,21 @@ impl MapArea {
         }
     }
 
-    pub fn new_shared(start_vaddr: VirtAddr, shared_paddr_vec: Vec<PhysAddr>, flags: MemFlags) -> Self {
+    pub fn new_shared(
+        start_vaddr: VirtAddr,
+        size: usize,
+        shmid: Option<usize>,
+        frames: ShmFrames,
+        flags: MemFlags,
+    ) -> Self {
         assert!(start_vaddr.is_aligned());
-        let mut mapping = BTreeMap::new();
-        for i in 0..shared_paddr_vec.len() {
-            mapping.insert(start_vaddr.as_usize() + i * PAGE_SIZE, shared_paddr_vec[i].as_usize());
-        }
+        assert!(is_aligned(size, PAGE_SIZE));
+        assert!(size <= frames.len() * PAGE_SIZE);
         Self {
             start: start_vaddr,
-            size: shared_paddr_vec.len() * PAGE_SIZE,
+            size,
             flags,
-            mapper: Mapper::Shared(mapping),
+            mapper: Mapper::Shared { shmid, frames },
         }
     }
 
@@ -102,7 +111,16 @@ impl MapArea {
                 }
                 Mapper::Framed(new_frames)
             }
-            Mapper::Shared(mapping) => Mapper::Shared(mapping.clone()),
+            Mapper::Shared { shmid, frames } => {
+                // the copy is another attach of the segment
+                if let Some(shmid) = shmid {
+                    dup_shm_seg(*shmid, frames);
+                }
+                Mapper::Shared {
+                    shmid: *shmid,
+                    frames: frames.clone(),
+                }
+            }
         };
         Self {
             start: self.start,
@@ -120,10 +138,9 @@ impl MapArea {
                 Entry::Occupied(e) => e.get().start_paddr(),
                 Entry::Vacant(e) => e.insert(PhysFrame::alloc_zero().unwrap()).start_paddr(),
             },
-            Mapper::Shared(mapping) => match mapping.entry(vaddr.as_usize()) {
-                Entry::Occupied(e) => PhysAddr::new(*e.get()),
-                Entry::Vacant(_) => panic!("Vacant entry in shared mapper!"),
-            },
+            Mapper::Shared { frames, .. } => {
+                frames[(vaddr.as_usize() - self.start.as_usize()) / PAGE_SIZE].start_paddr()
+            }
         }
     }
 
@@ -262,9 +279,17 @@ impl MemorySet {
         (entry, ustack_top)
     }
 
+    /// Unmaps all areas, and detaches the shared memory segments.
     pub fn clear(&mut self) {
         for area in self.areas.values_mut() {
             self.pt.unmap_area(area);
+            if let Mapper::Shared {
+                shmid: Some(shmid),
+                frames,
+            } = &area.mapper
+            {
+                detach_shm_seg(*shmid, frames);
+            }
         }
         self.areas.clear();
     }
@@ -281,20 +306,114 @@ impl MemorySet {
         self.pt.root_paddr()
     }
 
-    pub fn map_shared_frames(&mut self, shared_paddr_vec: Vec<PhysAddr>) -> Option<VirtAddr> {
-        let va_opt = self.areas.values()
-            .map(|area| area.start.as_usize() + area.size)
-            .filter(|addr| *addr < USER_STACK_BASE)  // TODO: more robust
-            .max();
-        if let Some(va) = va_opt {
-            self.insert(MapArea::new_shared(
-                VirtAddr::new(va),
-                shared_paddr_vec,
-                MemFlags::READ | MemFlags::WRITE | MemFlags::USER,
-            ));
-            Some(VirtAddr::new(va))
-        } else {
-            None
+    /// Checks whether the user range `[start, start + size)` is mapped with
+    /// all the given `flags`.
+    pub fn check_user_range(&self, start: usize, size: usize, flags: MemFlags) -> bool {
+        let end = match start.checked_add(size) {
+            Some(end) if start >= USER_ASPACE_BASE && end <= USER_ASPACE_BASE + USER_ASPACE_SIZE => {
+                end
+            }
+            _ => return false,
+        };
+        let mut vaddr = align_down(start, PAGE_SIZE);
+        while vaddr < end {
+            match self.pt.query(VirtAddr::new(vaddr)) {
+                Some((_, f)) if f.contains(flags | MemFlags::USER) => vaddr += PAGE_SIZE,
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    /// Whether the user range `[start, start + size)` is free, which is in the
+    /// user address space and does not overlap any area.
+    fn is_free_user_range(&self, start: usize, size: usize) -> bool {
+        let end = match start.checked_add(size) {
+            Some(end) if start >= USER_ASPACE_BASE && end <= USER_ASPACE_BASE + USER_ASPACE_SIZE => {
+                end
+            }
+            _ => return false,
+        };
+        match self.areas.range(..VirtAddr::new(end)).next_back() {
+            Some((_, area)) => area.start.as_usize() + area.size <= start,
+            None => true,
+        }
+    }
+
+    /// Maps the first `size` bytes of the shared `frames` of the segment
+    /// `shmid` (or of a POSIX shared memory object) with `flags`, at the page
+    /// aligned address `start`, or after the other areas if it is `None`.
+    /// Fails if the range is not free.
+    pub fn map_shared_frames(
+        &mut self,
+        shmid: Option<usize>,
+        frames: ShmFrames,
+        size: usize,
+        start: Option<usize>,
+        flags: MemFlags,
+    ) -> Option<VirtAddr> {
+        assert!(is_aligned(start.unwrap_or(0), PAGE_SIZE));
+        let va = match start {
+            Some(va) => va,
+            None => self
+                .areas
+                .values()
+                .map(|area| area.start.as_usize() + area.size)
+                .filter(|addr| *addr < USER_STACK_BASE) // TODO: more robust
+                .max()?,
+        };
+        if !self.is_free_user_range(va, size) {
+            return None;
+        }
+        self.insert(MapArea::new_shared(
+            VirtAddr::new(va),
+            size,
+            shmid,
+            frames,
+            flags,
+        ));
+        Some(VirtAddr::new(va))
+    }
+
+    /// Unmaps the System V shared memory area that starts at `start`, which
+    /// was mapped by `map_shared_frames`, and detaches its segment. Fails if
+    /// there is no such area.
+    pub fn unmap_shared_frames(&mut self, start: VirtAddr) -> bool {
+        match self.areas.entry(start) {
+            Entry::Occupied(e)
+                if matches!(e.get().mapper, Mapper::Shared { shmid: Some(_), .. }) =>
+            {
+                let mut area = e.remove();
+                self.pt.unmap_area(&mut area);
+                instructions::flush_tlb_all();
+                if let Mapper::Shared {
+                    shmid: Some(shmid),
+                    frames,
+                } = &area.mapper
+                {
+                    detach_shm_seg(*shmid, frames);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Unmaps the POSIX shared memory area of `size` bytes that starts at
+    /// `start`, which was mapped by `map_shared_frames`. Fails if there is no
+    /// such area, as areas can not be unmapped partially.
+    pub fn unmap_shm_object(&mut self, start: VirtAddr, size: usize) -> bool {
+        match self.areas.entry(start) {
+            Entry::Occupied(e)
+                if e.get().size == size
+                    && matches!(e.get().mapper, Mapper::Shared { shmid: None, .. }) =>
+            {
+                let mut area = e.remove();
+                self.pt.unmap_area(&mut area);
+                instructions::flush_tlb_all();
+                true
+            }
+            _ => false,
         }
     }
 }
@@ -384,7 +503,9 @@ impl fmt::Debug for MapArea {
         match &self.mapper {
             Mapper::Framed(_) => s.field("mapper", &"Frame"),
             Mapper::Offset(off) => s.field("mapper", &alloc::format!("Offset({})", off)),
-            Mapper::Shared(_) => s.field("mapper", &"Shared"),
+            Mapper::Shared { shmid, .. } => {
+                s.field("mapper", &alloc::format!("Shared({:?})", shmid))
+            }
         }
         .finish()
     }
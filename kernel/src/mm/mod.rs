@@ -5,13 +5,16 @@ mod memory_set;
 mod paging;
 mod uaccess;
 mod shared_memory;
+mod shm_object;
 
-pub use address::{PhysAddr, VirtAddr};
+pub use address::{align_down, align_up, is_aligned, PhysAddr, VirtAddr};
 pub use frame_allocator::PhysFrame;
 pub use memory_set::{kernel_aspace, MapArea, MemorySet};
 pub use paging::{GenericPTE, PageTableImpl};
 pub use uaccess::{UserInOutPtr, UserInPtr, UserOutPtr};
-pub use shared_memory::{create_shm_seg, get_shm_seg_paddr_vec};
+pub use shared_memory::{attach_shm_seg, detach_shm_seg, get_shm_seg, remove_shm_seg};
+pub use shared_memory::{stat_shm_seg, ShmFrames, ShmidDs};
+pub use shm_object::{open_shm_object, unlink_shm_object, ShmObject, TruncateError};
 
 pub const PAGE_SIZE: usize = 0x1000;
 
@@ -33,4 +36,5 @@ pub fn init() {
     frame_allocator::init_frame_allocator();
     memory_set::init_kernel_aspace();
     shared_memory::init_shared_memory();
+    shm_object::init_shm_objects();
 }
@@ -1,16 +1,54 @@
+use alloc::sync::Arc;
 use alloc::vec::Vec;
-use crate::mm::{PAGE_SIZE, PhysAddr, PhysFrame};
-use crate::sync::{LazyInit, UPSafeCell};
 
-const IPC_PRIVATE: usize = 0;
+use crate::drivers::timer::get_time_ns;
+use crate::mm::{PhysFrame, PAGE_SIZE};
+use crate::sync::{LazyInit, Mutex};
 
-const SHM_MAX_PAGE_NUM: usize = 256;
+const IPC_PRIVATE: usize = 0;
+/// Creates the segment of the key if it does not exist.
+const IPC_CREAT: usize = 0o1000;
+/// Fails if the segment of the key exists, with `IPC_CREAT`.
+const IPC_EXCL: usize = 0o2000;
+
+pub(super) const SHM_MAX_PAGE_NUM: usize = 256;
+
+/// Physical frames of a segment. They are shared by the segment and the areas
+/// mapping it, so that they are freed only when none of them is left.
+///
+/// Each frame is counted as well, as the areas mapping a POSIX shared memory
+/// object keep the pages they map, while the object is resized.
+pub type ShmFrames = Arc<Vec<Arc<PhysFrame>>>;
+
+/// Status of a segment returned by `shmctl(IPC_STAT)`.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ShmidDs {
+    /// Key given to `shmget`.
+    pub shm_key: usize,
+    /// Size of the segment in bytes.
+    pub shm_segsz: usize,
+    /// PID of the creator.
+    pub shm_cpid: usize,
+    /// Number of current attaches.
+    pub shm_nattch: usize,
+    /// Time of the last attach, in milliseconds.
+    pub shm_atime: usize,
+    /// Time of the last detach, in milliseconds.
+    pub shm_dtime: usize,
+}
 
 struct ShmSeg {
     creator_pid: usize,
     valid: bool,
     key: usize,
-    page_frames: Vec<PhysFrame>,
+    size: usize,
+    page_frames: ShmFrames,
+    nattch: usize,
+    atime_ms: usize,
+    dtime_ms: usize,
+    /// Marked by `IPC_RMID`, the segment is destroyed after the last detach.
+    removed: bool,
 }
 
 impl ShmSeg {
@@ -19,34 +57,77 @@ impl ShmSeg {
             creator_pid: 0,
             valid: false,
             key: 0,
-            page_frames: Vec::new(),
+            size: 0,
+            page_frames: Arc::new(Vec::new()),
+            nattch: 0,
+            atime_ms: 0,
+            dtime_ms: 0,
+            removed: false,
+        }
+    }
+
+    /// Destroys the segment if it is removed and not attached any more. The
+    /// frames are freed once they are not mapped either.
+    fn try_destroy(&mut self) {
+        if self.removed && self.nattch == 0 {
+            *self = Self::zero_init();
         }
     }
 }
 
 pub struct ShmSegManager {
-    shm_segments: UPSafeCell<Vec<ShmSeg>>,
+    shm_segments: Mutex<Vec<ShmSeg>>,
 }
 
 pub static SHM_SEG_MANAGER: LazyInit<ShmSegManager> = LazyInit::new();
 
 pub fn init_shared_memory() {
     SHM_SEG_MANAGER.init_by(ShmSegManager {
-        shm_segments: unsafe { UPSafeCell::new(Vec::new()) },
+        shm_segments: Mutex::new(Vec::new()),
     });
 }
 
-pub fn create_shm_seg(creator_pid: usize, key: usize, size: usize, shmflg: usize) -> isize {
+fn time_ms() -> usize {
+    (get_time_ns() / 1_000_000) as usize
+}
+
+/// Gets the segment of `key`, or creates one as `shmflg` says. A new segment
+/// is always created for `IPC_PRIVATE`. The segment must be at least `size`
+/// bytes.
+pub fn get_shm_seg(creator_pid: usize, key: usize, size: usize, shmflg: usize) -> isize {
+    let mut segments = SHM_SEG_MANAGER.shm_segments.lock();
     if key != IPC_PRIVATE {
-        panic!("Unsupported key in syscall `shmget`! Currently only key = IPC_PRIVATE is supported.");
+        // segments marked for destruction can not be found any more
+        let found = segments
+            .iter()
+            .position(|seg| seg.valid && !seg.removed && seg.key == key);
+        if let Some(shmid) = found {
+            if shmflg & IPC_CREAT != 0 && shmflg & IPC_EXCL != 0 {
+                return -1;
+            }
+            if size > segments[shmid].size {
+                return -1;
+            }
+            return shmid as isize;
+        }
+        if shmflg & IPC_CREAT == 0 {
+            return -1;
+        }
     }
+    create_shm_seg(&mut segments, creator_pid, key, size)
+}
 
+fn create_shm_seg(
+    segments: &mut Vec<ShmSeg>,
+    creator_pid: usize,
+    key: usize,
+    size: usize,
+) -> isize {
     let page_num = (size + PAGE_SIZE - 1) / PAGE_SIZE;
     if page_num > SHM_MAX_PAGE_NUM || page_num <= 0 {
         return -1;
     }
 
-    let mut segments = SHM_SEG_MANAGER.shm_segments.exclusive_access();
     let mut shmid = segments.len();
     for i in 0..segments.len() {
         if !segments[i].valid {
@@ -58,31 +139,90 @@ pub fn create_shm_seg(creator_pid: usize, key: usize, size: usize, shmflg: usize
         segments.push(ShmSeg::zero_init());
     }
 
-    segments[shmid].creator_pid = creator_pid;
-    segments[shmid].valid = true;
-    segments[shmid].key = key;
-    segments[shmid].page_frames.clear();
+    let mut page_frames = Vec::new();
     for _ in 0..page_num {
-        segments[shmid].page_frames.push(PhysFrame::alloc_zero().unwrap());
+        page_frames.push(Arc::new(PhysFrame::alloc_zero().unwrap()));
     }
+    segments[shmid] = ShmSeg {
+        creator_pid,
+        valid: true,
+        key,
+        size,
+        page_frames: Arc::new(page_frames),
+        ..ShmSeg::zero_init()
+    };
 
     shmid as isize
 }
 
-pub fn get_shm_seg_paddr_vec(shmid: usize, shmaddr: usize, shmflg: usize) -> Option<Vec<PhysAddr>> {
-    if shmaddr != 0 {
-        panic!("Unsupported shmaddr in syscall `shmat`! Currently only shmaddr = 0 is supported.");
+/// Attaches the segment `shmid`, returns its frames to be mapped. Segments
+/// marked for destruction can not be attached any more.
+pub fn attach_shm_seg(shmid: usize) -> Option<ShmFrames> {
+    let mut segments = SHM_SEG_MANAGER.shm_segments.lock();
+    match segments.get_mut(shmid) {
+        Some(seg) if seg.valid && !seg.removed => {
+            seg.nattch += 1;
+            seg.atime_ms = time_ms();
+            Some(seg.page_frames.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Counts the copy of an attach of the segment `shmid` mapped with `frames`,
+/// when the address space is duplicated by `fork`. The segment may have been
+/// marked for destruction, but is still attached by the parent.
+pub fn dup_shm_seg(shmid: usize, frames: &ShmFrames) {
+    let mut segments = SHM_SEG_MANAGER.shm_segments.lock();
+    if let Some(seg) = segments.get_mut(shmid) {
+        if seg.valid && Arc::ptr_eq(&seg.page_frames, frames) {
+            seg.nattch += 1;
+            seg.atime_ms = time_ms();
+        }
     }
+}
 
-    let segments = SHM_SEG_MANAGER.shm_segments.exclusive_access();
-    if !segments[shmid].valid {
-        return None;
+/// Detaches the segment `shmid` mapped with `frames`, which is destroyed if it
+/// is marked and this is the last attach.
+///
+/// Nothing is done if the segment has been destroyed already, and `shmid` is
+/// reused by another one.
+pub fn detach_shm_seg(shmid: usize, frames: &ShmFrames) {
+    let mut segments = SHM_SEG_MANAGER.shm_segments.lock();
+    if let Some(seg) = segments.get_mut(shmid) {
+        if seg.valid && Arc::ptr_eq(&seg.page_frames, frames) {
+            seg.nattch = seg.nattch.saturating_sub(1);
+            seg.dtime_ms = time_ms();
+            seg.try_destroy();
+        }
     }
+}
 
-    let mut paddr_vec = Vec::new();
-    for i in 0..segments[shmid].page_frames.len() {
-        paddr_vec.push(segments[shmid].page_frames[i].start_paddr());
+/// Marks the segment `shmid` to be destroyed after the last detach, which
+/// may be now.
+pub fn remove_shm_seg(shmid: usize) -> bool {
+    let mut segments = SHM_SEG_MANAGER.shm_segments.lock();
+    match segments.get_mut(shmid) {
+        Some(seg) if seg.valid => {
+            seg.removed = true;
+            seg.try_destroy();
+            true
+        }
+        _ => false,
     }
+}
 
-    Some(paddr_vec)
-}
\ No newline at end of file
+pub fn stat_shm_seg(shmid: usize) -> Option<ShmidDs> {
+    let segments = SHM_SEG_MANAGER.shm_segments.lock();
+    match segments.get(shmid) {
+        Some(seg) if seg.valid => Some(ShmidDs {
+            shm_key: seg.key,
+            shm_segsz: seg.size,
+            shm_cpid: seg.creator_pid,
+            shm_nattch: seg.nattch,
+            shm_atime: seg.atime_ms,
+            shm_dtime: seg.dtime_ms,
+        }),
+        _ => None,
+    }
+}
@@ -0,0 +1,134 @@
+//! POSIX shared memory objects, which are opened by name and mapped by
+//! `mmap`, on the same frames as System V segments.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use super::shared_memory::{ShmFrames, SHM_MAX_PAGE_NUM};
+use super::{PhysFrame, PAGE_SIZE};
+use crate::sync::{LazyInit, Mutex};
+
+struct ShmObjectInner {
+    size: usize,
+    /// Frames of the pages, which are shared with the mappings of them.
+    frames: Vec<Arc<PhysFrame>>,
+}
+
+/// Reasons why a shared memory object can not be resized.
+pub enum TruncateError {
+    /// The size is too large, or there is not enough memory.
+    NoSpace,
+    /// The pages that would be removed are still mapped.
+    Mapped,
+}
+
+/// A shared memory object. It lives on after being unlinked, until it is
+/// neither opened nor mapped.
+pub struct ShmObject {
+    inner: Mutex<ShmObjectInner>,
+}
+
+/// Objects that are not unlinked, by name.
+static SHM_OBJECTS: LazyInit<Mutex<BTreeMap<String, Arc<ShmObject>>>> = LazyInit::new();
+
+pub fn init_shm_objects() {
+    SHM_OBJECTS.init_by(Mutex::new(BTreeMap::new()));
+}
+
+impl ShmObject {
+    fn new() -> Self {
+        Self {
+            inner: Mutex::new(ShmObjectInner {
+                size: 0,
+                frames: Vec::new(),
+            }),
+        }
+    }
+
+    /// Returns the frames of the pages that `len` bytes from the start span,
+    /// to be mapped. Fails if the object does not have that many pages.
+    pub fn frames(&self, len: usize) -> Option<ShmFrames> {
+        let page_num = (len + PAGE_SIZE - 1) / PAGE_SIZE;
+        let inner = self.inner.lock();
+        inner
+            .frames
+            .get(..page_num)
+            .map(|frames| Arc::new(frames.to_vec()))
+    }
+
+    /// Resizes the object to `size` bytes, the new bytes are zero. It can
+    /// grow while it is mapped, but fails with `TruncateError::Mapped` if the
+    /// pages beyond `size` are still mapped.
+    pub fn truncate(&self, size: usize) -> Result<(), TruncateError> {
+        if size > SHM_MAX_PAGE_NUM * PAGE_SIZE {
+            return Err(TruncateError::NoSpace);
+        }
+        let mut guard = self.inner.lock();
+        let inner = &mut *guard;
+        let page_num = (size + PAGE_SIZE - 1) / PAGE_SIZE;
+        let frames = &mut inner.frames;
+        // the frames are counted by the mappings as well
+        if frames
+            .iter()
+            .skip(page_num)
+            .any(|f| Arc::strong_count(f) > 1)
+        {
+            return Err(TruncateError::Mapped);
+        }
+        let old_page_num = frames.len();
+        frames.truncate(page_num);
+        while frames.len() < page_num {
+            match PhysFrame::alloc_zero() {
+                Some(frame) => frames.push(Arc::new(frame)),
+                None => {
+                    frames.truncate(old_page_num);
+                    return Err(TruncateError::NoSpace);
+                }
+            }
+        }
+        // the tail of the last page is read as zero if the object grows again
+        if size < inner.size && size % PAGE_SIZE != 0 {
+            // written through the kernel address, as the page may be mapped
+            let tail = frames[size / PAGE_SIZE].start_paddr().into_kvaddr();
+            let offset = size % PAGE_SIZE;
+            unsafe { core::ptr::write_bytes(tail.as_mut_ptr().add(offset), 0, PAGE_SIZE - offset) };
+        }
+        inner.size = size;
+        Ok(())
+    }
+}
+
+/// Strips the leading `/` of `name`, which must have no other `/`.
+fn check_name(name: &str) -> Option<&str> {
+    match name.strip_prefix('/') {
+        Some(name) if !name.is_empty() && !name.contains('/') => Some(name),
+        _ => None,
+    }
+}
+
+/// Opens the object `name`, or creates an empty one if it does not exist and
+/// `create` is set. Fails if the object exists with `excl`.
+pub fn open_shm_object(name: &str, create: bool, excl: bool) -> Option<Arc<ShmObject>> {
+    let name = check_name(name)?;
+    let mut objects = SHM_OBJECTS.lock();
+    match objects.get(name) {
+        Some(_) if create && excl => None,
+        Some(object) => Some(object.clone()),
+        None if create => {
+            let object = Arc::new(ShmObject::new());
+            objects.insert(String::from(name), object.clone());
+            Some(object)
+        }
+        None => None,
+    }
+}
+
+/// Removes the name of the object `name`, which can not be opened any more.
+pub fn unlink_shm_object(name: &str) -> bool {
+    match check_name(name) {
+        Some(name) => SHM_OBJECTS.lock().remove(name).is_some(),
+        None => false,
+    }
+}
@@ -1,9 +1,7 @@
 mod lazy_init;
 mod mutex;
 mod spin;
-mod up;
 
 pub use lazy_init::LazyInit;
 pub use mutex::Mutex;
-pub use spin::SpinNoIrqLock;
-pub use up::UPSafeCell;
\ No newline at end of file
+pub use spin::SpinNoIrqLock;
\ No newline at end of file
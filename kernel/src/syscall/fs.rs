@@ -1,6 +1,8 @@
+use super::EINTR;
 use crate::drivers::uart::console_getchar;
 use crate::mm::{UserInPtr, UserOutPtr};
 use crate::task::CurrentTask;
+use crate::uintr;
 
 const FD_STDIN: usize = 0;
 const FD_STDOUT: usize = 1;
@@ -33,6 +35,8 @@ pub fn sys_read(fd: usize, mut buf: UserOutPtr<u8>, len: usize) -> isize {
                 if let Some(c) = console_getchar() {
                     buf.write(c);
                     return 1;
+                } else if uintr::has_pending() {
+                    return EINTR;
                 } else {
                     CurrentTask::get().yield_now();
                 }
@@ -43,3 +47,17 @@ pub fn sys_read(fd: usize, mut buf: UserOutPtr<u8>, len: usize) -> isize {
         }
     }
 }
+
+/// Only uintr and shared memory file descriptors can be closed.
+pub fn sys_close(fd: usize) -> isize {
+    match fd {
+        FD_STDIN | FD_STDOUT | FD_STDERR => -1,
+        _ => {
+            if CurrentTask::get().fds().lock().close(fd) {
+                0
+            } else {
+                -1
+            }
+        }
+    }
+}
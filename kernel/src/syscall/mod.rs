@@ -1,5 +1,8 @@
 const SYSCALL_READ: usize = 0;
 const SYSCALL_WRITE: usize = 1;
+const SYSCALL_CLOSE: usize = 3;
+const SYSCALL_MMAP: usize = 9;
+const SYSCALL_MUNMAP: usize = 11;
 const SYSCALL_YIELD: usize = 24;
 const SYSCALL_NANOSLEEP: usize = 35;
 const SYSCALL_GETPID: usize = 39;
@@ -8,17 +11,38 @@ const SYSCALL_FORK: usize = 57;
 const SYSCALL_EXEC: usize = 59;
 const SYSCALL_EXIT: usize = 60;
 const SYSCALL_WAITPID: usize = 61;
+const SYSCALL_FTRUNCATE: usize = 77;
 const SYSCALL_GET_TIME_MS: usize = 96;
 const SYSCALL_CLOCK_GETTIME: usize = 228;
 const SYSCALL_SHMGET: usize = 233;
 const SYSCALL_SHMAT: usize = 234;
 const SYSCALL_SHMDT: usize = 235;
 const SYSCALL_SHMCTL: usize = 236;
-const SYSCALL_UINTR_REGISTER_RECEIVER: usize = 301;
-const SYSCALL_UINTR_REGISTER_LINK: usize = 302;
-const SYSCALL_UINTR_REGISTER_SENDER: usize = 303;
 const SYSCALL_UINTR_NOTICE: usize = 304;
 const SYSCALL_UINTR_UIRET: usize = 305;
+const SYSCALL_UINTR_REGISTER_IRQ: usize = 306;
+const SYSCALL_UINTR_IRQ_ACK: usize = 307;
+const SYSCALL_UINTR_STATS: usize = 308;
+const SYSCALL_UINTR_STUI: usize = 309;
+const SYSCALL_UINTR_SET_TIMER: usize = 310;
+const SYSCALL_UINTR_CLUI: usize = 311;
+const SYSCALL_UINTR_GRANT: usize = 312;
+const SYSCALL_UINTR_REVOKE: usize = 313;
+const SYSCALL_UINTR_REGISTER_HANDLER: usize = 449;
+const SYSCALL_UINTR_UNREGISTER_HANDLER: usize = 450;
+const SYSCALL_UINTR_VECTOR_FD: usize = 451;
+const SYSCALL_UINTR_REGISTER_SENDER: usize = 452;
+const SYSCALL_UINTR_UNREGISTER_SENDER: usize = 453;
+const SYSCALL_UINTR_WAIT: usize = 454;
+const SYSCALL_SHM_OPEN: usize = 455;
+const SYSCALL_SHM_UNLINK: usize = 456;
+
+/// Returned by the waiting syscalls that are interrupted by a user interrupt,
+/// like `-EINTR` of Linux.
+const EINTR: isize = -4;
+/// Returned by `ftruncate` if it would shrink a shared memory object below one
+/// of its mappings, like `-EBUSY` of Linux.
+const EBUSY: isize = -16;
 
 mod fs;
 mod task;
@@ -33,13 +57,8 @@ use self::shm::*;
 use self::uintr::*;
 use crate::arch::{instructions, TrapFrame};
 
-pub fn syscall(
-    tf: &mut TrapFrame,
-    syscall_id: usize,
-    arg0: usize,
-    arg1: usize,
-    arg2: usize,
-) -> isize {
+pub fn syscall(tf: &mut TrapFrame, syscall_id: usize, args: [usize; 6]) -> isize {
+    let [arg0, arg1, arg2, arg3, arg4, arg5] = args;
     instructions::enable_irqs();
     debug!(
         "syscall {} enter <= ({:#x}, {:#x}, {:#x})",
@@ -48,6 +67,9 @@ pub fn syscall(
     let ret = match syscall_id {
         SYSCALL_READ => sys_read(arg0, arg1.into(), arg2),
         SYSCALL_WRITE => sys_write(arg0, arg1.into(), arg2),
+        SYSCALL_CLOSE => sys_close(arg0),
+        SYSCALL_MMAP => sys_mmap(arg0, arg1, arg2, arg3, arg4, arg5),
+        SYSCALL_MUNMAP => sys_munmap(arg0, arg1),
         SYSCALL_YIELD => sys_yield(),
         SYSCALL_NANOSLEEP => sys_nanosleep(arg0.into()),
         SYSCALL_GETPID => sys_getpid(),
@@ -56,17 +78,31 @@ pub fn syscall(
         SYSCALL_EXEC => sys_exec(arg0.into(), tf),
         SYSCALL_EXIT => sys_exit(arg0 as i32),
         SYSCALL_WAITPID => sys_waitpid(arg0 as isize, arg1.into()),
+        SYSCALL_FTRUNCATE => sys_ftruncate(arg0, arg1),
         SYSCALL_GET_TIME_MS => sys_get_time_ms(),
         SYSCALL_CLOCK_GETTIME => sys_clock_gettime(arg0, arg1.into()),
         SYSCALL_SHMGET => sys_shmget(arg0, arg1, arg2),
         SYSCALL_SHMAT => sys_shmat(arg0, arg1, arg2),
-        SYSCALL_SHMDT => sys_shmdt(),
-        SYSCALL_SHMCTL => sys_shmctl(),
-        SYSCALL_UINTR_REGISTER_RECEIVER => sys_uintr_register_receiver(arg0),
-        SYSCALL_UINTR_REGISTER_LINK => sys_uintr_register_link(arg0, arg1.into()),
-        SYSCALL_UINTR_REGISTER_SENDER => sys_uintr_register_sender(arg0, arg1.into()),
+        SYSCALL_SHMDT => sys_shmdt(arg0),
+        SYSCALL_SHMCTL => sys_shmctl(arg0, arg1, arg2),
+        SYSCALL_SHM_OPEN => sys_shm_open(arg0.into(), arg1, arg2),
+        SYSCALL_SHM_UNLINK => sys_shm_unlink(arg0.into()),
         SYSCALL_UINTR_NOTICE => sys_uintr_notice(arg0),
-        SYSCALL_UINTR_UIRET => sys_uintr_uiret(),
+        SYSCALL_UINTR_UIRET => sys_uintr_uiret(tf),
+        SYSCALL_UINTR_REGISTER_IRQ => sys_uintr_register_irq(arg0, arg1, arg2),
+        SYSCALL_UINTR_IRQ_ACK => sys_uintr_irq_ack(arg0),
+        SYSCALL_UINTR_STATS => sys_uintr_stats(arg0, arg1.into()),
+        SYSCALL_UINTR_STUI => sys_uintr_stui(),
+        SYSCALL_UINTR_SET_TIMER => sys_uintr_set_timer(arg0, arg1, arg2),
+        SYSCALL_UINTR_CLUI => sys_uintr_clui(),
+        SYSCALL_UINTR_GRANT => sys_uintr_grant(arg0, arg1),
+        SYSCALL_UINTR_REVOKE => sys_uintr_revoke(arg0, arg1),
+        SYSCALL_UINTR_REGISTER_HANDLER => sys_uintr_register_handler(arg0, arg1),
+        SYSCALL_UINTR_UNREGISTER_HANDLER => sys_uintr_unregister_handler(arg0),
+        SYSCALL_UINTR_VECTOR_FD => sys_uintr_vector_fd(arg0, arg1),
+        SYSCALL_UINTR_REGISTER_SENDER => sys_uintr_register_sender(arg0, arg1),
+        SYSCALL_UINTR_UNREGISTER_SENDER => sys_uintr_unregister_sender(arg0, arg1),
+        SYSCALL_UINTR_WAIT => sys_uintr_wait(arg0),
         _ => {
             println!("Unsupported syscall_id: {}", syscall_id);
             crate::task::CurrentTask::get().exit(-1);
@@ -1,17 +1,52 @@
-use crate::mm::{create_shm_seg, get_shm_seg_paddr_vec};
-use crate::task::CurrentTask;
+use super::EBUSY;
+use crate::config::{USER_ASPACE_BASE, USER_ASPACE_SIZE};
+use crate::mm::{align_down, align_up, is_aligned, MemFlags, ShmidDs, VirtAddr, PAGE_SIZE};
+use crate::mm::{attach_shm_seg, detach_shm_seg, get_shm_seg, remove_shm_seg, stat_shm_seg};
+use crate::mm::{open_shm_object, unlink_shm_object, TruncateError, UserInPtr, UserOutPtr};
+use crate::task::{CurrentTask, File};
 
 pub fn sys_shmget(key: usize, size: usize, shmflg: usize) -> isize {
-    create_shm_seg(CurrentTask::get().pid().as_usize(), key, size, shmflg)
+    get_shm_seg(CurrentTask::get().pid().as_usize(), key, size, shmflg)
 }
 
+/// Removes the segment after the last detach.
+const IPC_RMID: usize = 0;
+/// Gets the status of the segment.
+const IPC_STAT: usize = 2;
+
+/// Attaches the segment read-only.
+const SHM_RDONLY: usize = 0o10000;
+/// Rounds `shmaddr` down to a multiple of `SHMLBA`.
+const SHM_RND: usize = 0o20000;
+/// Alignment of the attach address.
+const SHMLBA: usize = PAGE_SIZE;
+
+/// Attaches the segment `shmid` at `shmaddr`, or at an address chosen by the
+/// kernel if it is 0. Fails if `shmaddr` is not aligned without `SHM_RND`, or
+/// the segment would overlap an existing mapping.
 pub fn sys_shmat(shmid: usize, shmaddr: usize, shmflg: usize) -> isize {
-    let paddr_vec = get_shm_seg_paddr_vec(shmid, shmaddr, shmflg);
-    if let Some(shared_paddr_vec) = paddr_vec {
-        let start_addr = CurrentTask::get().map_shared_frames(shared_paddr_vec);
+    let start = if shmflg & SHM_RND != 0 {
+        align_down(shmaddr, SHMLBA)
+    } else if is_aligned(shmaddr, SHMLBA) {
+        shmaddr
+    } else {
+        return -1;
+    };
+    let mut flags = MemFlags::READ | MemFlags::USER;
+    if shmflg & SHM_RDONLY == 0 {
+        flags |= MemFlags::WRITE;
+    }
+
+    let frames = attach_shm_seg(shmid);
+    if let Some(frames) = frames {
+        let start = if start != 0 { Some(start) } else { None };
+        let size = frames.len() * PAGE_SIZE;
+        let start_addr =
+            CurrentTask::get().map_shared_frames(Some(shmid), frames.clone(), size, start, flags);
         if let Some(addr) = start_addr {
             addr.as_usize() as isize
         } else {
+            detach_shm_seg(shmid, &frames);
             -1
         }
     } else {
@@ -19,10 +54,196 @@ pub fn sys_shmat(shmid: usize, shmaddr: usize, shmflg: usize) -> isize {
     }
 }
 
-pub fn sys_shmdt() -> isize {
-    -1
+/// Detaches the shared memory segment attached at `shmaddr`.
+pub fn sys_shmdt(shmaddr: usize) -> isize {
+    if !(USER_ASPACE_BASE..USER_ASPACE_BASE + USER_ASPACE_SIZE).contains(&shmaddr) {
+        return -1;
+    }
+    if CurrentTask::get().unmap_shared_frames(VirtAddr::new(shmaddr)) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// Controls the segment `shmid` with `cmd`, which is `IPC_RMID` or `IPC_STAT`.
+/// The status is written to `buf` for `IPC_STAT`.
+pub fn sys_shmctl(shmid: usize, cmd: usize, buf: usize) -> isize {
+    match cmd {
+        IPC_RMID if remove_shm_seg(shmid) => 0,
+        IPC_STAT => match stat_shm_seg(shmid) {
+            Some(ds) => {
+                let mut buf: UserOutPtr<ShmidDs> = buf.into();
+                buf.write(ds);
+                0
+            }
+            None => -1,
+        },
+        _ => -1,
+    }
 }
 
-pub fn sys_shmctl() -> isize {
-    -1
-}
\ No newline at end of file
+/// Access modes of `shm_open`, which must be read-only or read-write.
+const O_ACCMODE: usize = 0o3;
+const O_RDONLY: usize = 0;
+const O_RDWR: usize = 0o2;
+/// Creates the object if it does not exist.
+const O_CREAT: usize = 0o100;
+/// Fails if the object exists, with `O_CREAT`.
+const O_EXCL: usize = 0o200;
+/// Truncates the object to 0 bytes, with `O_RDWR`.
+const O_TRUNC: usize = 0o1000;
+
+/// Names of shared memory objects are shorter than this.
+const MAX_NAME_LEN: usize = 256;
+
+const PROT_READ: usize = 0x1;
+const PROT_WRITE: usize = 0x2;
+const PROT_EXEC: usize = 0x4;
+const MAP_SHARED: usize = 0x01;
+/// Maps at exactly `addr`, which must be free.
+const MAP_FIXED: usize = 0x10;
+
+fn read_name(name: UserInPtr<u8>) -> Option<([u8; MAX_NAME_LEN], usize)> {
+    let (buf, len) = name.read_str::<MAX_NAME_LEN>();
+    // the name may have been cut
+    (len < MAX_NAME_LEN - 1).then(|| (buf, len))
+}
+
+/// Opens the shared memory object `name`, which starts with `/`, returns a
+/// file descriptor of it. Permissions are not supported, and `mode` is
+/// ignored.
+pub fn sys_shm_open(name: UserInPtr<u8>, oflag: usize, _mode: usize) -> isize {
+    if oflag & !(O_ACCMODE | O_CREAT | O_EXCL | O_TRUNC) != 0 {
+        return -1;
+    }
+    let writable = match oflag & O_ACCMODE {
+        O_RDONLY => false,
+        O_RDWR => true,
+        _ => return -1,
+    };
+    if oflag & O_TRUNC != 0 && !writable {
+        return -1;
+    }
+    let (name_buf, len) = match read_name(name) {
+        Some(name) => name,
+        None => return -1,
+    };
+    let name = match core::str::from_utf8(&name_buf[..len]) {
+        Ok(name) => name,
+        Err(_) => return -1,
+    };
+    let object = match open_shm_object(name, oflag & O_CREAT != 0, oflag & O_EXCL != 0) {
+        Some(object) => object,
+        None => return -1,
+    };
+    if oflag & O_TRUNC != 0 && object.truncate(0).is_err() {
+        return -1;
+    }
+    let file = File::Shm { object, writable };
+    CurrentTask::get().fds().lock().add(file) as isize
+}
+
+/// Removes the name of the shared memory object `name`. The object is freed
+/// when it is neither opened nor mapped.
+pub fn sys_shm_unlink(name: UserInPtr<u8>) -> isize {
+    let (name_buf, len) = match read_name(name) {
+        Some(name) => name,
+        None => return -1,
+    };
+    match core::str::from_utf8(&name_buf[..len]) {
+        Ok(name) if unlink_shm_object(name) => 0,
+        _ => -1,
+    }
+}
+
+/// Resizes the shared memory object `fd` opened read-write to `length` bytes.
+/// It may grow while it is mapped, but `EBUSY` is returned if the pages beyond
+/// `length` are still mapped.
+pub fn sys_ftruncate(fd: usize, length: usize) -> isize {
+    let object = match CurrentTask::get().fds().lock().get(fd) {
+        Some(File::Shm {
+            object,
+            writable: true,
+        }) => object,
+        _ => return -1,
+    };
+    match object.truncate(length) {
+        Ok(()) => 0,
+        Err(TruncateError::Mapped) => EBUSY,
+        Err(TruncateError::NoSpace) => -1,
+    }
+}
+
+/// Maps `len` bytes of the shared memory object `fd` at `addr` with
+/// `MAP_FIXED`, or at an address chosen by the kernel. Only `MAP_SHARED`
+/// mappings from offset 0, within the size of the object, are supported.
+pub fn sys_mmap(
+    addr: usize,
+    len: usize,
+    prot: usize,
+    flags: usize,
+    fd: usize,
+    offset: usize,
+) -> isize {
+    if flags & !(MAP_SHARED | MAP_FIXED) != 0 || flags & MAP_SHARED == 0 {
+        return -1;
+    }
+    if prot & !(PROT_READ | PROT_WRITE | PROT_EXEC) != 0 || prot & PROT_READ == 0 {
+        return -1;
+    }
+    if len == 0 || offset != 0 {
+        return -1;
+    }
+    let curr = CurrentTask::get();
+    let (object, writable) = match curr.fds().lock().get(fd) {
+        Some(File::Shm { object, writable }) => (object, writable),
+        _ => return -1,
+    };
+    if prot & PROT_WRITE != 0 && !writable {
+        return -1;
+    }
+    let frames = match object.frames(len) {
+        Some(frames) => frames,
+        None => return -1,
+    };
+    let start = if flags & MAP_FIXED != 0 {
+        if addr == 0 || !is_aligned(addr, PAGE_SIZE) {
+            return -1;
+        }
+        Some(addr)
+    } else {
+        None
+    };
+
+    let mut mflags = MemFlags::READ | MemFlags::USER;
+    if prot & PROT_WRITE != 0 {
+        mflags |= MemFlags::WRITE;
+    }
+    if prot & PROT_EXEC != 0 {
+        mflags |= MemFlags::EXECUTE;
+    }
+    let size = align_up(len, PAGE_SIZE);
+    match curr.map_shared_frames(None, frames, size, start, mflags) {
+        Some(addr) => addr.as_usize() as isize,
+        None => -1,
+    }
+}
+
+/// Unmaps the whole mapping of a shared memory object made by `sys_mmap` at
+/// `addr` with `len`. The object can be shrunk below the pages it mapped once
+/// they are not mapped any more.
+pub fn sys_munmap(addr: usize, len: usize) -> isize {
+    if !(USER_ASPACE_BASE..USER_ASPACE_BASE + USER_ASPACE_SIZE).contains(&addr)
+        || !is_aligned(addr, PAGE_SIZE)
+        || len == 0
+    {
+        return -1;
+    }
+    let size = align_up(len, PAGE_SIZE);
+    if CurrentTask::get().unmap_shm_object(VirtAddr::new(addr), size) {
+        0
+    } else {
+        -1
+    }
+}
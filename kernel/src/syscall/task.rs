@@ -1,7 +1,9 @@
 use super::time::TimeSpec;
+use super::EINTR;
 use crate::arch::TrapFrame;
 use crate::mm::{UserInPtr, UserOutPtr};
 use crate::task::{spawn_task, CurrentTask};
+use crate::uintr;
 
 const MAX_STR_LEN: usize = 256;
 
@@ -19,7 +21,10 @@ pub fn sys_getpid() -> isize {
 }
 
 pub fn sys_clone(newsp: usize, tf: &TrapFrame) -> isize {
-    let new_task = CurrentTask::get().new_clone(newsp, tf);
+    let curr = CurrentTask::get();
+    let new_task = curr.new_clone(newsp, tf);
+    // the sender table may have been reallocated to be shared
+    curr.set_uintr_context(curr.uintr().lock().context());
     let pid = new_task.pid().as_usize() as isize;
     spawn_task(new_task);
     pid
@@ -40,19 +45,32 @@ pub fn sys_exec(path: UserInPtr<u8>, tf: &mut TrapFrame) -> isize {
 
 /// If there is no child process has the same pid as the given, return -1.
 /// Else if there is a child process but it is still running, return -2.
+///
+/// A user interrupt receiver is blocked instead until the child exits, or
+/// `EINTR` is returned if a user interrupt is posted to it meanwhile.
 pub fn sys_waitpid(pid: isize, mut exit_code_ptr: UserOutPtr<i32>) -> isize {
+    let current = CurrentTask::get();
     let mut exit_code = 0;
-    let ret = CurrentTask::get().waitpid(pid, &mut exit_code);
+    let ret = match current.waitpid(pid, &mut exit_code) {
+        -2 => match uintr::block_while(|| current.has_running_children(pid)) {
+            Some(true) => current.waitpid(pid, &mut exit_code),
+            Some(false) => EINTR,
+            None => -2,
+        },
+        ret => ret,
+    };
     exit_code_ptr.write(exit_code);
     ret
 }
 
+/// Sleeps for the given time, or returns `EINTR` if a user interrupt is
+/// posted to the caller meanwhile.
 pub fn sys_nanosleep(req: UserInPtr<TimeSpec>) -> isize {
     use crate::drivers::timer::get_time_ns;
     let stop_time = get_time_ns() + req.read().total_nano_sec();
-    let current = CurrentTask::get();
-    while get_time_ns() < stop_time {
-        current.yield_now();
+    if uintr::sleep_until(stop_time) {
+        0
+    } else {
+        EINTR
     }
-    0
 }
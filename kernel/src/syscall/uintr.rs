@@ -1,25 +1,257 @@
-use crate::mm::{create_shm_seg, get_shm_seg_paddr_vec, UserOutPtr};
-use crate::task::CurrentTask;
+use alloc::sync::Arc;
 
-pub fn sys_uintr_register_receiver(handler: usize) -> isize {
-    -1
+use crate::arch::TrapFrame;
+use crate::mm::UserOutPtr;
+use crate::task::{self, CurrentTask, File};
+use crate::uintr::{self, UintrGrant, UintrStats, UintrVector, UINTR_NUM_VECTORS};
+
+pub fn sys_uintr_register_handler(handler: usize, flags: usize) -> isize {
+    if flags != 0 {
+        return -1;
+    }
+    let curr = CurrentTask::get();
+    let mut state = curr.uintr().lock();
+    if state.register_receiver(handler, &curr) {
+        curr.set_uintr_context(state.context());
+        0
+    } else {
+        -1
+    }
 }
 
-pub fn sys_uintr_register_link(vector: usize, mut shmem_id: UserOutPtr<usize>) -> isize {
-    let id = create_shm_seg(CurrentTask::get().pid().as_usize(), 0, 1024, 0);
-    shmem_id.write(id as usize);
-    id
+pub fn sys_uintr_unregister_handler(flags: usize) -> isize {
+    if flags != 0 {
+        return -1;
+    }
+    let curr = CurrentTask::get();
+    let mut state = curr.uintr().lock();
+    if state.unregister_receiver() {
+        curr.set_uintr_context(state.context());
+        0
+    } else {
+        -1
+    }
 }
 
-pub fn sys_uintr_register_sender(link_id: usize, mut shmem_id: UserOutPtr<usize>) -> isize {
-    shmem_id.write(link_id);
-    0
+/// Creates a uintr file descriptor for `vector` of the calling receiver.
+pub fn sys_uintr_vector_fd(vector: usize, flags: usize) -> isize {
+    if vector >= UINTR_NUM_VECTORS || flags != 0 {
+        return -1;
+    }
+    let curr = CurrentTask::get();
+    let receiver = match curr.uintr().lock().receiver() {
+        Some(receiver) => receiver.clone(),
+        None => return -1,
+    };
+    match UintrVector::new(receiver, vector) {
+        Some(file) => curr.fds().lock().add(File::Uintr(Arc::new(file))) as isize,
+        None => -1,
+    }
+}
+
+/// Registers the calling task as a sender of the uintr file descriptor `fd`,
+/// which may also be a grant, returns the UITT index to send user interrupts
+/// with.
+///
+/// Only the entries registered with a grant are invalidated by revoking it.
+pub fn sys_uintr_register_sender(fd: usize, flags: usize) -> isize {
+    if flags != 0 {
+        return -1;
+    }
+    let curr = CurrentTask::get();
+    let file = curr.fds().lock().get(fd);
+    let mut state = curr.uintr().lock();
+    let index = match file {
+        Some(File::Uintr(target)) => state.register_sender(target),
+        Some(File::UintrGrant(grant)) => state.register_grant(grant),
+        _ => return -1,
+    };
+    match index {
+        Some(index) => {
+            curr.set_uintr_context(state.context());
+            index as isize
+        }
+        None => -1,
+    }
+}
+
+pub fn sys_uintr_unregister_sender(fd: usize, flags: usize) -> isize {
+    if flags != 0 {
+        return -1;
+    }
+    let curr = CurrentTask::get();
+    let file = curr.fds().lock().get(fd);
+    let mut state = curr.uintr().lock();
+    let ok = match file {
+        Some(File::Uintr(target)) => state.unregister_sender(&target),
+        Some(File::UintrGrant(grant)) => state.unregister_grant(&grant),
+        _ => return -1,
+    };
+    if ok {
+        0
+    } else {
+        -1
+    }
+}
+
+/// Creates a revocable grant of the uintr file descriptor `fd`, returns the
+/// file descriptor of the grant. `fd` must be a vector of the calling
+/// receiver.
+pub fn sys_uintr_grant(fd: usize, flags: usize) -> isize {
+    if flags != 0 {
+        return -1;
+    }
+    let curr = CurrentTask::get();
+    let target = match curr.fds().lock().get_uintr(fd) {
+        Some(target) => target,
+        None => return -1,
+    };
+    match curr.uintr().lock().receiver() {
+        Some(receiver) if Arc::ptr_eq(receiver, target.receiver()) => {}
+        _ => return -1,
+    }
+    let grant = Arc::new(UintrGrant::new(target));
+    let fd = curr.fds().lock().add(File::UintrGrant(grant));
+    fd as isize
+}
+
+/// Revokes the grant `grant_fd` of a vector of the calling receiver. Notices
+/// of its senders fail from now on, and nothing is posted by them any more.
+pub fn sys_uintr_revoke(grant_fd: usize, flags: usize) -> isize {
+    if flags != 0 {
+        return -1;
+    }
+    let curr = CurrentTask::get();
+    let grant = match curr.fds().lock().get_uintr_grant(grant_fd) {
+        Some(grant) => grant,
+        None => return -1,
+    };
+    match curr.uintr().lock().receiver() {
+        Some(receiver) if Arc::ptr_eq(receiver, grant.target().receiver()) => {}
+        _ => return -1,
+    }
+    if grant.revoke() {
+        0
+    } else {
+        -1
+    }
 }
 
 pub fn sys_uintr_notice(index: usize) -> isize {
-    -1
+    if CurrentTask::get().uintr().lock().send(index) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// On success, returns the value of the return register in the restored
+/// context, so that it is kept when the syscall returns.
+pub fn sys_uintr_uiret(tf: &mut TrapFrame) -> isize {
+    if uintr::uiret(tf) {
+        tf.ret_value() as isize
+    } else {
+        -1
+    }
+}
+
+/// Enables user interrupts inside a handler, which are enabled again by
+/// `uiret` otherwise.
+pub fn sys_uintr_stui() -> isize {
+    uintr::stui();
+    0
 }
 
-pub fn sys_uintr_uiret() -> isize {
-    -1
-}
\ No newline at end of file
+/// Disables user interrupts, which are still waited for by `uintr_wait`.
+pub fn sys_uintr_clui() -> isize {
+    uintr::clui();
+    0
+}
+
+pub fn sys_uintr_wait(flags: usize) -> isize {
+    if flags == 0 && uintr::wait() {
+        0
+    } else {
+        -1
+    }
+}
+
+/// Forwards the device interrupt `irq` to the uintr file descriptor `fd`,
+/// which must be a vector of the calling receiver.
+pub fn sys_uintr_register_irq(irq: usize, fd: usize, flags: usize) -> isize {
+    if flags != 0 {
+        return -1;
+    }
+    let curr = CurrentTask::get();
+    let target = match curr.fds().lock().get_uintr(fd) {
+        Some(target) => target,
+        None => return -1,
+    };
+    match curr.uintr().lock().receiver() {
+        Some(receiver) if Arc::ptr_eq(receiver, target.receiver()) => {}
+        _ => return -1,
+    }
+    if uintr::register_irq(irq, target) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// Unmasks the device interrupt `irq` forwarded to the calling receiver, which
+/// is masked on each interrupt.
+pub fn sys_uintr_irq_ack(irq: usize) -> isize {
+    let curr = CurrentTask::get();
+    let receiver = match curr.uintr().lock().receiver() {
+        Some(receiver) => receiver.clone(),
+        None => return -1,
+    };
+    if uintr::ack_irq(irq, &receiver) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// The timer of `sys_uintr_set_timer` expires only once.
+const UINTR_TIMER_ONESHOT: usize = 1 << 0;
+
+/// Arms the timer of the calling receiver, which sends a user interrupt to the
+/// uintr file descriptor `fd` after `delay_ns`, and then every `delay_ns`
+/// unless `UINTR_TIMER_ONESHOT` is set in `flags`. The timer is disarmed if
+/// `delay_ns` is 0. `fd` must be a vector of the calling receiver.
+pub fn sys_uintr_set_timer(fd: usize, delay_ns: usize, flags: usize) -> isize {
+    if flags & !UINTR_TIMER_ONESHOT != 0 {
+        return -1;
+    }
+    let curr = CurrentTask::get();
+    let target = match curr.fds().lock().get_uintr(fd) {
+        Some(target) => target,
+        None => return -1,
+    };
+    match curr.uintr().lock().receiver() {
+        Some(receiver) if Arc::ptr_eq(receiver, target.receiver()) => {}
+        _ => return -1,
+    }
+    let periodic = flags & UINTR_TIMER_ONESHOT == 0;
+    uintr::set_timer(target, delay_ns as u64, periodic);
+    0
+}
+
+/// Gets the user interrupt statistics of the task `pid`, or of the calling
+/// task if `pid` is 0.
+pub fn sys_uintr_stats(pid: usize, mut stats: UserOutPtr<UintrStats>) -> isize {
+    let task = if pid == 0 {
+        Some(CurrentTask::get().clone())
+    } else {
+        task::find_task(pid)
+    };
+    match task {
+        Some(task) => {
+            let st = task.uintr().lock().stats();
+            stats.write(st);
+            0
+        }
+        None => -1,
+    }
+}
@@ -0,0 +1,78 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::mm::ShmObject;
+use crate::uintr::{UintrGrant, UintrVector};
+
+/// File descriptors are allocated after stdin, stdout and stderr.
+const FD_BASE: usize = 3;
+
+/// An object referred to by a file descriptor.
+#[derive(Clone)]
+pub enum File {
+    /// A vector of a uintr receiver.
+    Uintr(Arc<UintrVector>),
+    /// A revocable grant of a vector of a uintr receiver.
+    UintrGrant(Arc<UintrGrant>),
+    /// A POSIX shared memory object, which can be written if `writable`.
+    Shm {
+        object: Arc<ShmObject>,
+        writable: bool,
+    },
+}
+
+/// Per-process table of file descriptors.
+#[derive(Clone)]
+pub struct FdTable {
+    files: Vec<Option<File>>,
+}
+
+impl FdTable {
+    pub const fn new() -> Self {
+        Self { files: Vec::new() }
+    }
+
+    /// Adds `file` to the table, returns the allocated file descriptor.
+    pub fn add(&mut self, file: File) -> usize {
+        let idx = match self.files.iter().position(|f| f.is_none()) {
+            Some(idx) => idx,
+            None => {
+                self.files.push(None);
+                self.files.len() - 1
+            }
+        };
+        self.files[idx] = Some(file);
+        idx + FD_BASE
+    }
+
+    pub fn get(&self, fd: usize) -> Option<File> {
+        let idx = fd.checked_sub(FD_BASE)?;
+        self.files.get(idx).cloned().flatten()
+    }
+
+    /// Gets the uintr vector of `fd`, fails if it refers to something else.
+    pub fn get_uintr(&self, fd: usize) -> Option<Arc<UintrVector>> {
+        match self.get(fd)? {
+            File::Uintr(vector) => Some(vector),
+            _ => None,
+        }
+    }
+
+    /// Gets the uintr grant of `fd`, fails if it refers to something else.
+    pub fn get_uintr_grant(&self, fd: usize) -> Option<Arc<UintrGrant>> {
+        match self.get(fd)? {
+            File::UintrGrant(grant) => Some(grant),
+            _ => None,
+        }
+    }
+
+    /// Removes `fd` from the table. The senders registered with a uintr file
+    /// descriptor or a grant, and the mappings of a shared memory object are
+    /// not affected.
+    pub fn close(&mut self, fd: usize) -> bool {
+        match fd.checked_sub(FD_BASE) {
+            Some(idx) if idx < self.files.len() => self.files[idx].take().is_some(),
+            _ => false,
+        }
+    }
+}
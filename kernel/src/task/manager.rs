@@ -1,4 +1,4 @@
-use alloc::sync::Arc;
+use alloc::{format, string::String, sync::Arc};
 use core::cell::UnsafeCell;
 
 use super::schedule::{Scheduler, SimpleScheduler};
@@ -58,6 +58,20 @@ impl<S: Scheduler> TaskManager<S> {
         self.resched(curr_task);
     }
 
+    pub fn block_current(&mut self, curr_task: &CurrentTask) {
+        assert!(!curr_task.is_idle());
+        assert!(curr_task.state() == TaskState::Running);
+        curr_task.set_state(TaskState::Blocked);
+        self.resched(curr_task);
+    }
+
+    pub fn unblock_task(&mut self, t: &Arc<Task>) {
+        if t.state() == TaskState::Blocked {
+            t.set_state(TaskState::Ready);
+            self.scheduler.add_ready_task(t);
+        }
+    }
+
     pub fn exit_current(&mut self, curr_task: &CurrentTask, exit_code: i32) -> ! {
         assert!(!curr_task.is_idle());
         assert!(!curr_task.is_root());
@@ -65,6 +79,10 @@ impl<S: Scheduler> TaskManager<S> {
 
         curr_task.set_state(TaskState::Zombie);
         curr_task.set_exit_code(exit_code);
+        // wake up the parent if it is blocked in `waitpid`
+        if let Some(parent) = curr_task.parent.lock().upgrade() {
+            self.unblock_task(&parent);
+        }
 
         // Make all child tasks as the children of the root task
         {
@@ -85,8 +103,8 @@ impl<S: Scheduler> TaskManager<S> {
             return;
         }
         println!(
-            "{:>4} {:>4} {:>6} {:>4}  STATE",
-            "PID", "PPID", "#CHILD", "#REF",
+            "{:>4} {:>4} {:>6} {:>4} {:>14}  STATE",
+            "PID", "PPID", "#CHILD", "#REF", "UINTR(S/P/D)",
         );
         ROOT_TASK.traverse(&|t: &Arc<Task>| {
             let pid = t.pid().as_usize();
@@ -94,16 +112,24 @@ impl<S: Scheduler> TaskManager<S> {
             let children_count = t.children.lock().len();
             let state = t.state();
             let shared = if t.is_shared_with_parent() { 'S' } else { ' ' };
+            // the uintr state may be held by a task waiting for `TASK_MANAGER`
+            let uintr = match t.uintr().try_lock() {
+                Some(u) => {
+                    let stats = u.stats();
+                    format!("{}/{}/{}", stats.sent, stats.posted, stats.delivered)
+                }
+                None => String::from("?"),
+            };
             if let Some(p) = t.parent.lock().upgrade() {
                 let ppid = p.pid().as_usize();
                 println!(
-                    "{:>4}{}{:>4} {:>6} {:>4}  {:?}",
-                    pid, shared, ppid, children_count, ref_count, state
+                    "{:>4}{}{:>4} {:>6} {:>4} {:>14}  {:?}",
+                    pid, shared, ppid, children_count, ref_count, uintr, state
                 );
             } else {
                 println!(
-                    "{:>4} {:>4} {:>6} {:>4}  {:?}",
-                    pid, '-', children_count, ref_count, state
+                    "{:>4} {:>4} {:>6} {:>4} {:>14}  {:?}",
+                    pid, '-', children_count, ref_count, uintr, state
                 );
             }
         });
@@ -1,18 +1,26 @@
+mod fd;
 mod manager;
 mod schedule;
 mod structs;
 
+pub use fd::{FdTable, File};
 pub use structs::{CurrentTask, Task, TaskId};
 
-use alloc::sync::Arc;
+use alloc::{sync::Arc, vec::Vec};
+use core::cell::RefCell;
 use core::sync::atomic::{AtomicBool, Ordering};
 
 use self::manager::TASK_MANAGER;
 use self::structs::ROOT_TASK;
 use crate::arch::instructions;
+use crate::drivers::timer::get_time_ns;
+use crate::sync::Mutex;
 
 static TASK_INITED: AtomicBool = AtomicBool::new(false);
 
+/// Tasks blocked in `CurrentTask::sleep_until`, with their deadlines.
+static SLEEPING_TASKS: Mutex<Vec<(u64, Arc<Task>)>> = Mutex::new(Vec::new());
+
 pub fn is_init() -> bool {
     TASK_INITED.load(Ordering::SeqCst)
 }
@@ -59,9 +67,44 @@ pub fn spawn_task(task: Arc<Task>) {
     TASK_MANAGER.lock().spawn(task);
 }
 
+/// Finds the task with `pid`, which may be a zombie not waited yet.
+pub fn find_task(pid: usize) -> Option<Arc<Task>> {
+    let found = RefCell::new(None);
+    ROOT_TASK.traverse(&|t: &Arc<Task>| {
+        if t.pid().as_usize() == pid {
+            *found.borrow_mut() = Some(t.clone());
+        }
+    });
+    found.into_inner()
+}
+
+fn add_sleeping_task(task: &Arc<Task>, deadline_ns: u64) {
+    SLEEPING_TASKS.lock().push((deadline_ns, task.clone()));
+}
+
+fn remove_sleeping_task(task: &Arc<Task>) {
+    SLEEPING_TASKS.lock().retain(|(_, t)| !Arc::ptr_eq(t, task));
+}
+
+/// Wakes up the sleeping tasks whose deadlines have passed.
+///
+/// Called on each timer interrupt.
+pub fn wake_sleeping_tasks() {
+    let now = get_time_ns();
+    for (deadline_ns, task) in SLEEPING_TASKS.lock().iter() {
+        if *deadline_ns <= now {
+            task.unblock();
+        }
+    }
+}
+
 pub fn run() -> ! {
     println!("Running tasks...");
     instructions::enable_irqs();
-    CurrentTask::get().yield_now(); // current task is idle at this time
-    unreachable!("root task exit!");
+    loop {
+        // current task is idle at this time, and is picked again only if all
+        // other tasks are blocked
+        CurrentTask::get().yield_now();
+        instructions::wait_for_ints();
+    }
 }
@@ -2,13 +2,16 @@ use alloc::sync::{Arc, Weak};
 use alloc::{boxed::Box, vec::Vec};
 use core::sync::atomic::{AtomicI32, AtomicU8, AtomicUsize, Ordering};
 
+use super::fd::FdTable;
 use super::manager::{TaskLockedCell, TASK_MANAGER};
 use crate::arch::{instructions, TaskContext, TrapFrame};
 use crate::config::KERNEL_STACK_SIZE;
+use crate::drivers::timer::get_time_ns;
 use crate::loader;
-use crate::mm::{kernel_aspace, MemorySet, PhysAddr, VirtAddr};
+use crate::mm::{kernel_aspace, MemFlags, MemorySet, ShmFrames, VirtAddr};
 use crate::percpu::PerCpu;
 use crate::sync::{LazyInit, Mutex};
+use crate::uintr::{UintrContext, UintrState};
 
 pub(super) static ROOT_TASK: LazyInit<Arc<Task>> = LazyInit::new();
 
@@ -27,6 +30,7 @@ pub enum TaskState {
     Ready = 1,
     Running = 2,
     Zombie = 3,
+    Blocked = 4,
 }
 
 pub struct Task {
@@ -41,6 +45,8 @@ pub struct Task {
     ctx: TaskLockedCell<TaskContext>,
 
     vm: Option<Arc<Mutex<MemorySet>>>,
+    uintr: Mutex<UintrState>,
+    fds: Arc<Mutex<FdTable>>,
     pub(super) parent: Mutex<Weak<Task>>,
     pub(super) children: Mutex<Vec<Arc<Task>>>,
 }
@@ -70,6 +76,7 @@ impl From<u8> for TaskState {
             1 => Self::Ready,
             2 => Self::Running,
             3 => Self::Zombie,
+            4 => Self::Blocked,
             _ => panic!("invalid task state: {}", state),
         }
     }
@@ -89,6 +96,8 @@ impl Task {
             ctx: TaskLockedCell::new(TaskContext::default()),
 
             vm: None,
+            uintr: Mutex::new(UintrState::new()),
+            fds: Arc::new(Mutex::new(FdTable::new())),
             parent: Mutex::new(Weak::default()),
             children: Mutex::new(Vec::new()),
         }
@@ -157,6 +166,10 @@ impl Task {
             false,
         );
         t.vm = Some(vm);
+        // Threads share the file descriptors and the sender table, but each
+        // can be a receiver of its own.
+        t.uintr = Mutex::new(self.uintr.lock().new_thread());
+        t.fds = self.fds.clone();
 
         let t = Arc::new(t);
         self.add_child(&t);
@@ -172,6 +185,9 @@ impl Task {
             .get_mut()
             .init(task_entry as _, t.kstack.top(), vm.page_table_root(), false);
         t.vm = Some(Arc::new(Mutex::new(vm)));
+        // Like Linux, the child inherits the file descriptors, but none of the
+        // user interrupt registrations.
+        t.fds = Arc::new(Mutex::new(self.fds.lock().clone()));
 
         let t = Arc::new(t);
         self.add_child(&t);
@@ -218,6 +234,20 @@ impl Task {
         &self.ctx
     }
 
+    pub const fn uintr(&self) -> &Mutex<UintrState> {
+        &self.uintr
+    }
+
+    /// File descriptors, which are shared by threads of a process.
+    pub fn fds(&self) -> &Mutex<FdTable> {
+        &self.fds
+    }
+
+    /// Wakes up the task if it is blocked.
+    pub fn unblock(self: &Arc<Self>) {
+        TASK_MANAGER.lock().unblock_task(self)
+    }
+
     pub(super) fn traverse(self: &Arc<Self>, func: &impl Fn(&Arc<Task>)) {
         func(self);
         for c in self.children.lock().iter() {
@@ -225,8 +255,37 @@ impl Task {
         }
     }
 
-    pub fn map_shared_frames(&self, shared_paddr_vec: Vec<PhysAddr>) -> Option<VirtAddr> {
-        self.vm.as_ref().unwrap().lock().map_shared_frames(shared_paddr_vec)
+    pub fn map_shared_frames(
+        &self,
+        shmid: Option<usize>,
+        frames: ShmFrames,
+        size: usize,
+        start: Option<usize>,
+        flags: MemFlags,
+    ) -> Option<VirtAddr> {
+        self.vm
+            .as_ref()
+            .unwrap()
+            .lock()
+            .map_shared_frames(shmid, frames, size, start, flags)
+    }
+
+    pub fn unmap_shared_frames(&self, start: VirtAddr) -> bool {
+        self.vm.as_ref().unwrap().lock().unmap_shared_frames(start)
+    }
+
+    pub fn unmap_shm_object(&self, start: VirtAddr, size: usize) -> bool {
+        self.vm
+            .as_ref()
+            .unwrap()
+            .lock()
+            .unmap_shm_object(start, size)
+    }
+
+    pub fn check_user_range(&self, start: usize, size: usize, flags: MemFlags) -> bool {
+        self.vm
+            .as_ref()
+            .map_or(false, |vm| vm.lock().check_user_range(start, size, flags))
     }
 }
 
@@ -258,8 +317,38 @@ impl<'a> CurrentTask<'a> {
         TASK_MANAGER.lock().yield_current(self)
     }
 
+    /// Blocks the current task until it is woken up by `Task::unblock`, unless
+    /// `cond` returns `false`. The condition is checked with the task manager
+    /// locked, so that a wake-up right after it can not be lost.
+    pub fn block_if(&self, cond: impl FnOnce() -> bool) {
+        let mut m = TASK_MANAGER.lock();
+        if cond() {
+            m.block_current(self);
+        }
+    }
+
+    /// Blocks the current task until `deadline_ns`, or until it is woken up
+    /// by `Task::unblock` with `cond` returning `false`.
+    pub fn sleep_until(&self, deadline_ns: u64, cond: impl Fn() -> bool) {
+        super::add_sleeping_task(self.0, deadline_ns);
+        while get_time_ns() < deadline_ns && cond() {
+            self.block_if(|| get_time_ns() < deadline_ns && cond());
+        }
+        super::remove_sleeping_task(self.0);
+    }
+
+    /// Drops the user interrupt registrations of the current task, so that
+    /// no one can send user interrupts to it any more. The uintr file
+    /// descriptors are not closed.
+    fn clear_uintr(&self) {
+        let mut state = self.uintr().lock();
+        state.clear();
+        self.set_uintr_context(state.context());
+    }
+
     pub fn exit(&self, exit_code: i32) -> ! {
         info!("task exit with code {}", exit_code);
+        self.clear_uintr();
         if let Some(vm) = self.vm.as_ref() {
             if Arc::strong_count(vm) == 1 {
                 vm.lock().clear(); // drop memory set before lock
@@ -268,10 +357,18 @@ impl<'a> CurrentTask<'a> {
         TASK_MANAGER.lock().exit_current(self, exit_code)
     }
 
+    /// Updates the user interrupt state in the task context, which is also
+    /// loaded into the hardware since the task is running.
+    pub fn set_uintr_context(&self, uintr_ctx: UintrContext) {
+        let _guard = TASK_MANAGER.lock();
+        unsafe { (*self.context().as_ptr()).set_uintr(uintr_ctx) };
+    }
+
     pub fn exec(&self, path: &str, tf: &mut TrapFrame) -> isize {
         assert!(!self.is_kernel_task());
         assert_eq!(Arc::strong_count(self.vm.as_ref().unwrap()), 1);
         if let Some(elf_data) = loader::get_app_data_by_name(path) {
+            self.clear_uintr();
             let mut vm = self.vm.as_ref().unwrap().lock();
             vm.clear();
             let (entry, ustack_top) = vm.load_user(elf_data);
@@ -283,6 +380,17 @@ impl<'a> CurrentTask<'a> {
         }
     }
 
+    /// Whether `waitpid` with `pid` would return -2, as none of the children
+    /// it waits for has exited yet.
+    pub fn has_running_children(&self, pid: isize) -> bool {
+        let children = self.children.lock();
+        let mut waited = children
+            .iter()
+            .filter(|t| pid == -1 || t.pid().as_usize() == pid as usize)
+            .peekable();
+        waited.peek().is_some() && waited.all(|t| t.state() != TaskState::Zombie)
+    }
+
     pub fn waitpid(&self, pid: isize, exit_code: &mut i32) -> isize {
         let mut children = self.children.lock();
         let mut found_pid = false;
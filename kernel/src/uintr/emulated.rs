@@ -0,0 +1,104 @@
+//! Software emulation of user interrupts.
+//!
+//! A pending user interrupt is noticed on the next return to user mode, where
+//! the interrupted context is pushed onto the user stack and the task is
+//! redirected to its handler.
+
+use core::mem::{align_of, size_of};
+
+use super::{UintrBackend, Upid, UINTR_FRAME_ALIGN, UINTR_STACK_ADJUST};
+use crate::arch::TrapFrame;
+use crate::mm::{MemFlags, UserInPtr, UserOutPtr};
+use crate::task::CurrentTask;
+
+pub struct EmulatedUintr;
+
+impl UintrBackend for EmulatedUintr {
+    fn name(&self) -> &'static str {
+        "emulated"
+    }
+
+    fn post(&self, upid: &Upid, vector: usize) {
+        // no notification is needed, since the pending interrupts are checked
+        // on every return to user mode
+        upid.post(vector);
+    }
+
+    /// The interrupted context is pushed onto the user stack, and `tf` is
+    /// redirected to the registered handler, with the vector as its first
+    /// argument.
+    fn deliver_pending(&self, tf: &mut TrapFrame) {
+        let curr = CurrentTask::get();
+        let (handler, vector) = {
+            let mut state = curr.uintr().lock();
+            let receiver = match state.receiver() {
+                Some(r) if state.uif => r,
+                _ => return,
+            };
+            let vector = match receiver.upid().take_pending() {
+                Some(vector) => vector,
+                None => return,
+            };
+            receiver.counters().record_delivery(vector);
+            let handler = receiver.handler();
+            state.uif = false;
+            (handler, vector)
+        };
+
+        // On x86_64, the vector is also pushed below the saved context, like
+        // what the hardware does, so the handler is entered with a call-like
+        // stack.
+        let frame_size = size_of::<TrapFrame>();
+        let vector_size = if cfg!(target_arch = "x86_64") { 8 } else { 0 };
+        let frame = tf
+            .user_sp()
+            .checked_sub(UINTR_STACK_ADJUST + frame_size)
+            .map(|addr| addr & !(UINTR_FRAME_ALIGN - 1));
+        let sp = frame.and_then(|addr| addr.checked_sub(vector_size));
+        match (frame, sp) {
+            (Some(frame), Some(sp))
+                if curr.check_user_range(sp, frame + frame_size - sp, MemFlags::WRITE) =>
+            {
+                UserOutPtr::<TrapFrame>::from(frame).write(*tf);
+                if vector_size != 0 {
+                    UserOutPtr::<u64>::from(sp).write(vector as u64);
+                }
+                tf.redirect_user(handler, sp, vector);
+            }
+            _ => {
+                warn!(
+                    "Failed to push user interrupt frame, user_sp={:#x}, kernel killed it.",
+                    tf.user_sp()
+                );
+                curr.exit(-1);
+            }
+        }
+    }
+
+    /// The interrupted context is popped from the user stack into `tf`, and
+    /// user interrupts are enabled again, so that the next pending one (if
+    /// any) is delivered on the way back to user mode.
+    fn uiret(&self, tf: &mut TrapFrame) -> bool {
+        let curr = CurrentTask::get();
+        let frame = tf.user_sp();
+        if frame % align_of::<TrapFrame>() != 0
+            || !curr.check_user_range(frame, size_of::<TrapFrame>(), MemFlags::READ)
+        {
+            return false;
+        }
+        let saved = UserInPtr::<TrapFrame>::from(frame).read();
+        if !tf.restore_user(&saved) {
+            return false;
+        }
+        curr.uintr().lock().uif = true;
+        true
+    }
+
+    fn stui(&self) {
+        CurrentTask::get().uintr().lock().uif = true;
+    }
+
+    fn clui(&self) {
+        CurrentTask::get().uintr().lock().uif = false;
+    }
+}
@@ -0,0 +1,50 @@
+use alloc::sync::Arc;
+
+use super::{UintrReceiver, BACKEND};
+
+/// A vector of a receiver, which is referred to by a uintr file descriptor,
+/// and can be registered by senders to send user interrupts with.
+pub struct UintrVector {
+    receiver: Arc<UintrReceiver>,
+    vector: usize,
+}
+
+impl UintrVector {
+    /// Allocates `vector` of `receiver`, fails if it is already in use.
+    pub fn new(receiver: Arc<UintrReceiver>, vector: usize) -> Option<Self> {
+        if receiver.alloc_vector(vector) {
+            Some(Self { receiver, vector })
+        } else {
+            None
+        }
+    }
+
+    pub const fn receiver(&self) -> &Arc<UintrReceiver> {
+        &self.receiver
+    }
+
+    pub const fn vector(&self) -> usize {
+        self.vector
+    }
+
+    /// Posts a user interrupt with the vector to the receiver, fails if it has
+    /// been unregistered.
+    pub fn post(&self) -> bool {
+        if !self.receiver.is_active() {
+            return false;
+        }
+        let upid = self.receiver.upid();
+        self.receiver
+            .counters()
+            .record_post(self.vector, upid.is_pending(self.vector));
+        BACKEND.post(upid, self.vector);
+        self.receiver.wake();
+        true
+    }
+}
+
+impl Drop for UintrVector {
+    fn drop(&mut self) {
+        self.receiver.free_vector(self.vector);
+    }
+}
@@ -0,0 +1,92 @@
+//! Revocable grants of vectors.
+//!
+//! A grant is created by the receiver from a uintr file descriptor, and is a
+//! file descriptor itself, which is passed to forked children like the other
+//! ones. Senders registered with a grant can not send user interrupts any more
+//! after the receiver revokes it.
+//!
+//! Only grants are revocable: a sender registered with the uintr file
+//! descriptor itself, including in a forked child it has been inherited by,
+//! keeps its access until it unregisters or exits. Receivers that need to
+//! withdraw access pass grants rather than the descriptor to the senders.
+//!
+//! Revocation only stops further posting. The user interrupts posted through
+//! the grant before are still delivered, since the pending bit of a vector
+//! can not tell which of its senders posted it.
+
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+
+use super::{UintrVector, Uitt};
+use crate::sync::Mutex;
+
+struct GrantState {
+    revoked: bool,
+    /// Sender tables with entries registered with the grant, which are
+    /// invalidated on revocation.
+    senders: Vec<Weak<Mutex<Uitt>>>,
+}
+
+/// A vector granted by its receiver to the holders of the file descriptor.
+pub struct UintrGrant {
+    target: Arc<UintrVector>,
+    state: Mutex<GrantState>,
+}
+
+impl UintrGrant {
+    pub fn new(target: Arc<UintrVector>) -> Self {
+        Self {
+            target,
+            state: Mutex::new(GrantState {
+                revoked: false,
+                senders: Vec::new(),
+            }),
+        }
+    }
+
+    pub const fn target(&self) -> &Arc<UintrVector> {
+        &self.target
+    }
+
+    /// Adds the sender table `uitt`, which is about to have an entry for the
+    /// grant. Fails if the grant has been revoked.
+    ///
+    /// The table must be locked until the entry is allocated, so that it is
+    /// invalidated by a revocation in between.
+    pub fn add_sender(&self, uitt: &Arc<Mutex<Uitt>>) -> bool {
+        let mut state = self.state.lock();
+        if state.revoked {
+            return false;
+        }
+        state.senders.retain(|s| s.strong_count() > 0);
+        state.senders.push(Arc::downgrade(uitt));
+        true
+    }
+
+    /// Posts a user interrupt to the target, fails if the grant has been
+    /// revoked.
+    ///
+    /// Nothing is posted after the revocation returns, as the state is locked
+    /// while posting.
+    pub fn post(&self) -> bool {
+        let state = self.state.lock();
+        !state.revoked && self.target.post()
+    }
+
+    /// Revokes the grant, and invalidates the entries of its senders. Fails if
+    /// it has been revoked already.
+    pub fn revoke(self: &Arc<Self>) -> bool {
+        let senders = {
+            let mut state = self.state.lock();
+            if state.revoked {
+                return false;
+            }
+            state.revoked = true;
+            core::mem::take(&mut state.senders)
+        };
+        for uitt in senders.iter().filter_map(Weak::upgrade) {
+            uitt.lock().invalidate(self);
+        }
+        true
+    }
+}
@@ -0,0 +1,71 @@
+//! Forwarding of device interrupts to user space as user interrupts.
+
+use alloc::{sync::Arc, vec::Vec};
+
+use super::{UintrReceiver, UintrVector};
+use crate::drivers::interrupt::{self, IrqHandlerResult};
+use crate::sync::Mutex;
+
+/// Interrupt vectors of the forwarded IRQs, and their targets.
+static IRQ_TARGETS: Mutex<Vec<(usize, Arc<UintrVector>)>> = Mutex::new(Vec::new());
+
+fn find_target(targets: &[(usize, Arc<UintrVector>)], vector: usize) -> Option<usize> {
+    targets.iter().position(|(v, _)| *v == vector)
+}
+
+/// Forwards the device `irq` to `target`, instead of handling it in the
+/// kernel. Fails if the IRQ is handled by the kernel, or is forwarded to
+/// another active receiver.
+///
+/// The IRQ is masked after each interrupt, until it is acknowledged by
+/// [`ack_irq`].
+pub fn register_irq(irq: usize, target: Arc<UintrVector>) -> bool {
+    let vector = match interrupt::irq_to_vector(irq) {
+        Some(vector) => vector,
+        None => return false,
+    };
+    let mut targets = IRQ_TARGETS.lock();
+    match find_target(&targets, vector) {
+        Some(idx) if targets[idx].1.receiver().is_active() => return false,
+        Some(idx) => targets[idx].1 = target,
+        None if interrupt::has_handler(vector) => return false,
+        None => {
+            targets.push((vector, target));
+            interrupt::register_handler(vector, forward_irq);
+        }
+    }
+    interrupt::set_enable(vector, true);
+    true
+}
+
+/// Unmasks the device `irq` forwarded to `receiver`, after the interrupt has
+/// been handled in user space.
+pub fn ack_irq(irq: usize, receiver: &Arc<UintrReceiver>) -> bool {
+    let vector = match interrupt::irq_to_vector(irq) {
+        Some(vector) => vector,
+        None => return false,
+    };
+    let targets = IRQ_TARGETS.lock();
+    match find_target(&targets, vector) {
+        Some(idx) if Arc::ptr_eq(targets[idx].1.receiver(), receiver) => {
+            interrupt::set_enable(vector, true);
+            true
+        }
+        _ => false,
+    }
+}
+
+fn forward_irq(vector: usize) -> IrqHandlerResult {
+    interrupt::set_enable(vector, false);
+    let mut targets = IRQ_TARGETS.lock();
+    match find_target(&targets, vector) {
+        Some(idx) if targets[idx].1.post() => IrqHandlerResult::Reschedule,
+        Some(idx) => {
+            // the receiver has been unregistered, keep the IRQ masked
+            targets.remove(idx);
+            interrupt::unregister_handler(vector);
+            IrqHandlerResult::NoReschedule
+        }
+        None => IrqHandlerResult::NoReschedule,
+    }
+}
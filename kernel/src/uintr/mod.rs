@@ -0,0 +1,369 @@
+//! User interrupts (UINTR).
+//!
+//! User interrupts are handled by the CPU if it supports them, and emulated
+//! in software otherwise. The backend is chosen at boot.
+
+mod emulated;
+mod fd;
+mod grant;
+mod irq;
+mod receiver;
+mod sender;
+mod stats;
+mod timer;
+
+pub use fd::UintrVector;
+pub use grant::UintrGrant;
+pub use irq::{ack_irq, register_irq};
+pub use receiver::{UintrReceiver, Upid};
+pub use sender::Uitt;
+pub use stats::{UintrCounters, UintrStats};
+pub use timer::{expire_timers, set_timer};
+
+use alloc::{sync::Arc, vec::Vec};
+
+use self::emulated::EmulatedUintr;
+use self::timer::cancel_timer;
+use crate::arch::TrapFrame;
+use crate::config::{USER_ASPACE_BASE, USER_ASPACE_SIZE};
+use crate::sync::{LazyInit, Mutex};
+use crate::task::{CurrentTask, Task};
+
+/// Number of user interrupt vectors a receiver can be sent.
+pub const UINTR_NUM_VECTORS: usize = 64;
+
+/// Bytes skipped below the interrupted stack pointer before pushing the
+/// interrupted context, to preserve the red zone of the System V ABI.
+const UINTR_STACK_ADJUST: usize = if cfg!(target_arch = "x86_64") { 128 } else { 0 };
+
+const UINTR_FRAME_ALIGN: usize = 16;
+
+/// Operations on user interrupts that are done differently by the hardware
+/// and the software emulation.
+pub trait UintrBackend: Sync {
+    fn name(&self) -> &'static str;
+
+    /// Initializes the UPID of a new receiver.
+    fn init_upid(&self, _upid: &Upid) {}
+
+    /// Posts a user interrupt with `vector` to the receiver of `upid`.
+    fn post(&self, upid: &Upid, vector: usize);
+
+    /// Whether there are user interrupts pending for the current task, which
+    /// is the receiver of `upid`.
+    fn has_pending(&self, upid: &Upid) -> bool {
+        upid.has_pending()
+    }
+
+    /// Delivers a pending user interrupt to the current task, before returning
+    /// to user mode with `tf`.
+    fn deliver_pending(&self, tf: &mut TrapFrame);
+
+    /// Returns from a user interrupt handler of the current task to the
+    /// interrupted context saved on the user stack.
+    fn uiret(&self, tf: &mut TrapFrame) -> bool;
+
+    /// Sets the user interrupt flag of the current task, like `STUI`.
+    fn stui(&self);
+
+    /// Clears the user interrupt flag of the current task, like `CLUI`.
+    fn clui(&self);
+}
+
+static BACKEND: LazyInit<&'static dyn UintrBackend> = LazyInit::new();
+
+/// Receivers blocked in `uintr_wait`.
+static WAITING_RECEIVERS: Mutex<Vec<Arc<UintrReceiver>>> = Mutex::new(Vec::new());
+
+/// The architectural user interrupt state of a task, which is switched along
+/// with its `TaskContext`, and loaded into the IA32_UINTR_* MSRs on CPUs with
+/// user interrupt support.
+#[derive(Debug, Clone, Copy)]
+pub struct UintrContext {
+    /// Address of the user interrupt handler.
+    pub handler: usize,
+    /// Bytes skipped below the user stack pointer on delivery.
+    pub stack_adjust: usize,
+    /// Address of the UPID if the task is a receiver, or 0.
+    pub upid_addr: usize,
+    /// Address of the UITT if the task is a sender, or 0.
+    pub uitt_addr: usize,
+    /// Number of entries in the UITT.
+    pub uitt_size: usize,
+    /// User interrupt requests that have been recognized but not delivered.
+    pub uirr: u64,
+}
+
+impl UintrContext {
+    pub const fn new() -> Self {
+        Self {
+            handler: 0,
+            stack_adjust: 0,
+            upid_addr: 0,
+            uitt_addr: 0,
+            uitt_size: 0,
+            uirr: 0,
+        }
+    }
+}
+
+/// Per-task user interrupt state.
+pub struct UintrState {
+    receiver: Option<Arc<UintrReceiver>>,
+    /// User interrupt flag. It is cleared when a user interrupt is delivered
+    /// and set again by `uiret`, so that handlers are never nested.
+    uif: bool,
+    /// The sender table, shared by the threads of a process.
+    uitt: Arc<Mutex<Uitt>>,
+    counters: Arc<UintrCounters>,
+}
+
+impl UintrState {
+    pub fn new() -> Self {
+        Self {
+            receiver: None,
+            uif: true,
+            uitt: Arc::new(Mutex::new(Uitt::new())),
+            counters: Arc::new(UintrCounters::new()),
+        }
+    }
+
+    /// Creates the state of a new thread, which shares the sender table with
+    /// the current one, but is not a receiver.
+    ///
+    /// The table is fully allocated from now on, so that its address and size
+    /// in the contexts of the threads stay valid. The context of the current
+    /// thread needs to be updated after this.
+    pub fn new_thread(&self) -> Self {
+        self.uitt.lock().alloc_all();
+        Self {
+            uitt: self.uitt.clone(),
+            ..Self::new()
+        }
+    }
+
+    pub fn receiver(&self) -> Option<&Arc<UintrReceiver>> {
+        self.receiver.as_ref()
+    }
+
+    /// Returns the architectural state to be loaded when the task is running.
+    pub fn context(&self) -> UintrContext {
+        let mut ctx = UintrContext::new();
+        if let Some(receiver) = &self.receiver {
+            ctx.handler = receiver.handler();
+            ctx.stack_adjust = UINTR_STACK_ADJUST;
+            ctx.upid_addr = receiver.upid() as *const _ as usize;
+        }
+        let uitt = self.uitt.lock();
+        if !uitt.is_empty() {
+            ctx.uitt_addr = uitt.as_ptr() as usize;
+            ctx.uitt_size = uitt.len();
+        }
+        ctx
+    }
+
+    /// Registers `task` as a receiver with the user interrupt `handler`.
+    pub fn register_receiver(&mut self, handler: usize, task: &Arc<Task>) -> bool {
+        if self.receiver.is_some() || !is_user_addr(handler) {
+            return false;
+        }
+        let receiver = Arc::new(UintrReceiver::new(
+            handler,
+            Arc::downgrade(task),
+            self.counters.clone(),
+        ));
+        BACKEND.init_upid(receiver.upid());
+        self.receiver = Some(receiver);
+        self.uif = true;
+        true
+    }
+
+    /// Unregisters the task as a receiver. The vectors of it can not be sent
+    /// user interrupts any more, even if there are still file descriptors.
+    pub fn unregister_receiver(&mut self) -> bool {
+        match self.receiver.take() {
+            Some(receiver) => {
+                receiver.deactivate();
+                cancel_timer(&receiver);
+                self.uif = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drops all registrations of the task, as a receiver and as a sender.
+    /// The sender table is left to the other threads if it is shared.
+    pub fn clear(&mut self) {
+        self.unregister_receiver();
+        self.uif = true;
+        self.uitt = Arc::new(Mutex::new(Uitt::new()));
+    }
+
+    /// Registers the task as a sender of `target`, returns the index of the
+    /// allocated UITT entry.
+    pub fn register_sender(&mut self, target: Arc<UintrVector>) -> Option<usize> {
+        self.uitt.lock().alloc(target, None)
+    }
+
+    /// Registers the task as a sender of the vector granted by `grant`,
+    /// returns the index of the allocated UITT entry. Fails if the grant has
+    /// been revoked.
+    pub fn register_grant(&mut self, grant: Arc<UintrGrant>) -> Option<usize> {
+        let mut uitt = self.uitt.lock();
+        if !grant.add_sender(&self.uitt) {
+            return None;
+        }
+        uitt.alloc(grant.target().clone(), Some(grant))
+    }
+
+    /// Unregisters the task as a sender of `target`.
+    pub fn unregister_sender(&mut self, target: &Arc<UintrVector>) -> bool {
+        self.uitt.lock().free(target)
+    }
+
+    /// Unregisters the task as a sender with `grant`.
+    pub fn unregister_grant(&mut self, grant: &Arc<UintrGrant>) -> bool {
+        self.uitt.lock().free_grant(grant)
+    }
+
+    /// Sends a user interrupt through the UITT entry `index`, like `SENDUIPI`.
+    pub fn send(&self, index: usize) -> bool {
+        let sent = self.uitt.lock().send(index);
+        if sent {
+            self.counters.record_send();
+        }
+        sent
+    }
+
+    /// Returns the user interrupt statistics of the task.
+    pub fn stats(&self) -> UintrStats {
+        self.counters.stats()
+    }
+}
+
+fn is_user_addr(vaddr: usize) -> bool {
+    (USER_ASPACE_BASE..USER_ASPACE_BASE + USER_ASPACE_SIZE).contains(&vaddr)
+}
+
+/// Delivers a pending user interrupt to the current task, if there is one.
+///
+/// Must be called right before returning to user mode with `tf`.
+pub fn deliver_pending(tf: &mut TrapFrame) {
+    BACKEND.deliver_pending(tf)
+}
+
+/// Returns from a user interrupt handler, like `UIRET`.
+pub fn uiret(tf: &mut TrapFrame) -> bool {
+    BACKEND.uiret(tf)
+}
+
+/// Enables user interrupts of the current task without returning from a
+/// handler, like `STUI`.
+pub fn stui() {
+    BACKEND.stui()
+}
+
+/// Disables user interrupts of the current task, like `CLUI`. They stay
+/// pending until enabled again, which can still be waited for.
+pub fn clui() {
+    BACKEND.clui()
+}
+
+/// Whether a user interrupt is pending and can be delivered to the current
+/// task, which cuts short its waits in the kernel.
+pub fn has_pending() -> bool {
+    let curr = CurrentTask::get();
+    let state = curr.uintr().lock();
+    match state.receiver() {
+        Some(receiver) if state.uif => BACKEND.has_pending(receiver.upid()),
+        _ => false,
+    }
+}
+
+/// Blocks the current task until a user interrupt is pending for it, which is
+/// then delivered on the return to user mode.
+pub fn wait() -> bool {
+    let curr = CurrentTask::get();
+    let receiver = match curr.uintr().lock().receiver() {
+        Some(receiver) => receiver.clone(),
+        None => return false,
+    };
+    WAITING_RECEIVERS.lock().push(receiver.clone());
+    while !BACKEND.has_pending(receiver.upid()) {
+        curr.block_if(|| !BACKEND.has_pending(receiver.upid()));
+    }
+    WAITING_RECEIVERS
+        .lock()
+        .retain(|r| !Arc::ptr_eq(r, &receiver));
+    true
+}
+
+/// Runs the blocking `wait` of the current task, which is given whether a user
+/// interrupt is pending for the task, and is woken up when one is posted.
+/// Returns `None` without running it if the task is not a receiver with user
+/// interrupts enabled, or whether no user interrupt is pending afterwards.
+fn wait_interruptible(wait: impl FnOnce(&dyn Fn() -> bool)) -> Option<bool> {
+    let curr = CurrentTask::get();
+    let receiver = {
+        let state = curr.uintr().lock();
+        state.receiver().filter(|_| state.uif).cloned()?
+    };
+    let pending = || BACKEND.has_pending(receiver.upid());
+    WAITING_RECEIVERS.lock().push(receiver.clone());
+    wait(&pending);
+    WAITING_RECEIVERS
+        .lock()
+        .retain(|r| !Arc::ptr_eq(r, &receiver));
+    Some(!pending())
+}
+
+/// Sleeps until `deadline_ns`, unless a user interrupt is posted to the
+/// current task meanwhile, in which case `false` is returned.
+pub fn sleep_until(deadline_ns: u64) -> bool {
+    let curr = CurrentTask::get();
+    match wait_interruptible(|pending| curr.sleep_until(deadline_ns, || !pending())) {
+        Some(not_interrupted) => not_interrupted,
+        None => {
+            curr.sleep_until(deadline_ns, || true);
+            true
+        }
+    }
+}
+
+/// Blocks the current task while `cond` returns `true`, unless a user
+/// interrupt is posted to it meanwhile, in which case `Some(false)` is
+/// returned. The task must be woken up by `Task::unblock` when `cond` may have
+/// changed.
+///
+/// Returns `None` without blocking if the task is not a receiver with user
+/// interrupts enabled, as nothing could cut the wait short then.
+pub fn block_while(cond: impl Fn() -> bool) -> Option<bool> {
+    let curr = CurrentTask::get();
+    wait_interruptible(|pending| {
+        while cond() && !pending() {
+            curr.block_if(|| cond() && !pending());
+        }
+    })
+}
+
+/// Wakes up the blocked receivers with user interrupts posted.
+///
+/// It is called periodically, since `SENDUIPI` executed by the hardware does
+/// not go through the kernel.
+pub fn wake_waiting_receivers() {
+    for receiver in WAITING_RECEIVERS.lock().iter() {
+        if receiver.upid().has_pending() {
+            receiver.wake();
+        }
+    }
+}
+
+pub fn init() {
+    let backend = crate::arch::uintr_backend().unwrap_or(&EmulatedUintr);
+    println!(
+        "Initializing user interrupts ({} backend)...",
+        backend.name()
+    );
+    BACKEND.init_by(backend);
+}
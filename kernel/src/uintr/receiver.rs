@@ -0,0 +1,171 @@
+use alloc::sync::{Arc, Weak};
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use super::UintrCounters;
+use crate::task::Task;
+
+/// Outstanding Notification (ON) bit in the notification control word.
+const UPID_ON: u64 = 1 << 0;
+/// Suppress Notification (SN) bit in the notification control word.
+const UPID_SN: u64 = 1 << 1;
+const UPID_NV_SHIFT: u64 = 16;
+const UPID_NV_MASK: u64 = 0xff << UPID_NV_SHIFT;
+const UPID_NDST_SHIFT: u64 = 32;
+const UPID_NDST_MASK: u64 = 0xffff_ffff << UPID_NDST_SHIFT;
+
+/// User Posted Interrupt Descriptor, which collects the user interrupts
+/// posted to a receiver until they are delivered.
+///
+/// It has the architectural layout, so that it can also be used by the CPU.
+#[repr(C, align(64))]
+pub struct Upid {
+    /// Notification control: ON, SN, notification vector and destination.
+    nc: AtomicU64,
+    /// Posted-interrupt requests, one bit for each user interrupt vector.
+    pir: AtomicU64,
+}
+
+impl Upid {
+    const fn new() -> Self {
+        Self {
+            nc: AtomicU64::new(0),
+            pir: AtomicU64::new(0),
+        }
+    }
+
+    /// Sets the vector of the ordinary interrupt used as the notification.
+    pub fn set_notification_vector(&self, vector: u8) {
+        self.update_nc(|nc| (nc & !UPID_NV_MASK) | (vector as u64) << UPID_NV_SHIFT);
+    }
+
+    /// Returns the NDST field, which identifies the CPU that notifications are
+    /// sent to in the format of the APIC mode.
+    pub fn notification_dest(&self) -> u32 {
+        ((self.nc.load(Ordering::Acquire) & UPID_NDST_MASK) >> UPID_NDST_SHIFT) as u32
+    }
+
+    /// Posts a user interrupt with `vector` and marks it pending, like what
+    /// `SENDUIPI` does.
+    ///
+    /// Returns `true` if a notification needs to be sent to the receiver.
+    pub fn post(&self, vector: usize) -> bool {
+        self.pir.fetch_or(1 << vector, Ordering::AcqRel);
+        self.nc.load(Ordering::Acquire) & UPID_SN == 0 && self.test_and_set_on()
+    }
+
+    /// Whether `vector` has been posted and not delivered yet.
+    pub fn is_pending(&self, vector: usize) -> bool {
+        self.pir.load(Ordering::Acquire) & (1 << vector) != 0
+    }
+
+    /// Whether there are posted-interrupt requests.
+    pub fn has_pending(&self) -> bool {
+        self.pir.load(Ordering::Acquire) != 0
+    }
+
+    /// Takes the highest pending vector out of the posted-interrupt requests,
+    /// which is the one the CPU delivers first from UIRR.
+    pub fn take_pending(&self) -> Option<usize> {
+        self.pir
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |pir| {
+                (pir != 0).then(|| pir & !highest_bit(pir))
+            })
+            .ok()
+            .map(|pir| highest_bit(pir).trailing_zeros() as usize)
+    }
+
+    /// Suppresses notifications while the receiver is not running.
+    pub fn suspend(&self) {
+        self.nc.fetch_or(UPID_SN, Ordering::AcqRel);
+    }
+
+    /// Allows notifications to be sent to the CPU of the NDST field `ndst`
+    /// again, when the receiver is running on that CPU.
+    ///
+    /// Returns `true` if a notification needs to be sent for the user
+    /// interrupts posted while suspended.
+    pub fn resume(&self, ndst: u32) -> bool {
+        self.update_nc(|nc| (nc & !(UPID_SN | UPID_NDST_MASK)) | (ndst as u64) << UPID_NDST_SHIFT);
+        self.pir.load(Ordering::Acquire) != 0 && self.test_and_set_on()
+    }
+
+    fn test_and_set_on(&self) -> bool {
+        self.nc.fetch_or(UPID_ON, Ordering::AcqRel) & UPID_ON == 0
+    }
+
+    fn update_nc(&self, f: impl Fn(u64) -> u64) {
+        self.nc
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |nc| Some(f(nc)))
+            .ok();
+    }
+}
+
+fn highest_bit(bits: u64) -> u64 {
+    1 << (63 - bits.leading_zeros())
+}
+
+/// A task registered to receive user interrupts.
+pub struct UintrReceiver {
+    handler: usize,
+    upid: Upid,
+    task: Weak<Task>,
+    /// Cleared when the receiver is unregistered, after which it can not be
+    /// sent user interrupts any more.
+    active: AtomicBool,
+    /// Vectors that have uintr file descriptors.
+    vectors: AtomicU64,
+    /// Statistics of the receiver task.
+    counters: Arc<UintrCounters>,
+}
+
+impl UintrReceiver {
+    pub const fn new(handler: usize, task: Weak<Task>, counters: Arc<UintrCounters>) -> Self {
+        Self {
+            handler,
+            upid: Upid::new(),
+            task,
+            active: AtomicBool::new(true),
+            vectors: AtomicU64::new(0),
+            counters,
+        }
+    }
+
+    pub const fn handler(&self) -> usize {
+        self.handler
+    }
+
+    pub const fn upid(&self) -> &Upid {
+        &self.upid
+    }
+
+    pub fn counters(&self) -> &UintrCounters {
+        &self.counters
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Acquire)
+    }
+
+    /// Stops receiving user interrupts, and suppresses notifications for the
+    /// ones still sent by hardware.
+    pub fn deactivate(&self) {
+        self.active.store(false, Ordering::Release);
+        self.upid.suspend();
+    }
+
+    /// Marks `vector` in use, returns `false` if it is already.
+    pub fn alloc_vector(&self, vector: usize) -> bool {
+        self.vectors.fetch_or(1 << vector, Ordering::AcqRel) & (1 << vector) == 0
+    }
+
+    pub fn free_vector(&self, vector: usize) {
+        self.vectors.fetch_and(!(1 << vector), Ordering::AcqRel);
+    }
+
+    /// Wakes up the receiver task if it is blocked in `uintr_wait`.
+    pub fn wake(&self) {
+        if let Some(task) = self.task.upgrade() {
+            task.unblock();
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use super::{UintrGrant, UintrVector};
+
+/// Maximum number of entries in a User Interrupt Target Table.
+const UITT_MAX_ENTRIES: usize = 256;
+
+/// An entry of the UITT in the architectural layout.
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+struct UittEntry {
+    valid: u8,
+    vector: u8,
+    reserved: [u8; 6],
+    upid_addr: u64,
+}
+
+impl UittEntry {
+    const INVALID: Self = Self {
+        valid: 0,
+        vector: 0,
+        reserved: [0; 6],
+        upid_addr: 0,
+    };
+}
+
+/// The target of an allocated entry, which keeps its UPID alive.
+#[derive(Clone)]
+struct UittTarget {
+    vector: Arc<UintrVector>,
+    /// The grant it is registered with, if any.
+    grant: Option<Arc<UintrGrant>>,
+}
+
+impl UittTarget {
+    fn post(&self) -> bool {
+        match &self.grant {
+            Some(grant) => grant.post(),
+            None => self.vector.post(),
+        }
+    }
+}
+
+/// User Interrupt Target Table. Each valid entry is a target that the task
+/// can send user interrupts to by its index.
+///
+/// Entries of revoked grants are invalid, but stay allocated until they are
+/// freed, so that their indices are not reused meanwhile.
+pub struct Uitt {
+    entries: Vec<UittEntry>,
+    /// Targets of the allocated entries.
+    targets: Vec<Option<UittTarget>>,
+}
+
+impl Uitt {
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            targets: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the address of the table, which changes after allocations.
+    pub fn as_ptr(&self) -> *const u8 {
+        self.entries.as_ptr() as _
+    }
+
+    /// Allocates all the entries, after which the table never moves or grows.
+    pub fn alloc_all(&mut self) {
+        self.entries.resize(UITT_MAX_ENTRIES, UittEntry::INVALID);
+        self.targets.resize(UITT_MAX_ENTRIES, None);
+    }
+
+    fn find(&self, f: impl Fn(&UittTarget) -> bool) -> Option<usize> {
+        self.targets
+            .iter()
+            .position(|t| matches!(t, Some(t) if f(t)))
+    }
+
+    /// Allocates an entry for `target`, returns its index. Fails if there is
+    /// already one. `grant` is the grant of `target` if it is registered with
+    /// one.
+    pub fn alloc(
+        &mut self,
+        target: Arc<UintrVector>,
+        grant: Option<Arc<UintrGrant>>,
+    ) -> Option<usize> {
+        if self.find(|t| Arc::ptr_eq(&t.vector, &target)).is_some() {
+            return None;
+        }
+        let entry = UittEntry {
+            valid: 1,
+            vector: target.vector() as u8,
+            upid_addr: target.receiver().upid() as *const _ as u64,
+            ..UittEntry::INVALID
+        };
+        let index = match self.targets.iter().position(|t| t.is_none()) {
+            Some(index) => index,
+            None if self.entries.len() < UITT_MAX_ENTRIES => {
+                self.entries.push(UittEntry::INVALID);
+                self.targets.push(None);
+                self.entries.len() - 1
+            }
+            None => return None,
+        };
+        self.entries[index] = entry;
+        self.targets[index] = Some(UittTarget {
+            vector: target,
+            grant,
+        });
+        Some(index)
+    }
+
+    fn free_index(&mut self, index: usize) {
+        self.entries[index] = UittEntry::INVALID;
+        self.targets[index] = None;
+    }
+
+    /// Frees the entry for `target`, which is registered without a grant.
+    pub fn free(&mut self, target: &Arc<UintrVector>) -> bool {
+        match self.find(|t| t.grant.is_none() && Arc::ptr_eq(&t.vector, target)) {
+            Some(index) => {
+                self.free_index(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Frees the entry registered with `grant`, even if it has been revoked.
+    pub fn free_grant(&mut self, grant: &Arc<UintrGrant>) -> bool {
+        match self.find(|t| matches!(&t.grant, Some(g) if Arc::ptr_eq(g, grant))) {
+            Some(index) => {
+                self.free_index(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Invalidates the entries registered with `grant` when it is revoked,
+    /// so that the CPU does not send user interrupts with them either.
+    pub fn invalidate(&mut self, grant: &Arc<UintrGrant>) {
+        for (entry, target) in self.entries.iter_mut().zip(&self.targets) {
+            if matches!(target, Some(UittTarget { grant: Some(g), .. }) if Arc::ptr_eq(g, grant)) {
+                *entry = UittEntry::INVALID;
+            }
+        }
+    }
+
+    /// Posts a user interrupt to the target of entry `index`.
+    pub fn send(&self, index: usize) -> bool {
+        match self.targets.get(index) {
+            Some(Some(target)) => target.post(),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use super::UINTR_NUM_VECTORS;
+use crate::drivers::timer::get_time_ns;
+
+/// User interrupt statistics of a task, in the layout returned to user space
+/// by `sys_uintr_stats`.
+///
+/// Only the user interrupts going through the kernel are counted, so with the
+/// hardware backend, `SENDUIPI` executed by user space is not, and deliveries
+/// are not either.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UintrStats {
+    /// Notices sent by the task as a sender.
+    pub sent: u64,
+    /// User interrupts posted to the task as a receiver.
+    pub posted: u64,
+    /// User interrupts delivered to the handler of the task.
+    pub delivered: u64,
+    /// Posts of vectors that were already pending, which are merged into one
+    /// delivery.
+    pub coalesced: u64,
+    /// Total time from the posts to the handler entries, in nanoseconds.
+    pub total_latency_ns: u64,
+    /// Maximum time from a post to the handler entry, in nanoseconds.
+    pub max_latency_ns: u64,
+}
+
+/// Per-task counters behind `UintrStats`. They are shared with the receivers
+/// of the task, so that they can be updated by senders without the lock of
+/// the task's `UintrState`, and are kept across registrations.
+pub struct UintrCounters {
+    sent: AtomicU64,
+    posted: AtomicU64,
+    delivered: AtomicU64,
+    coalesced: AtomicU64,
+    total_latency_ns: AtomicU64,
+    max_latency_ns: AtomicU64,
+    /// Time of the first post of each pending vector.
+    post_time_ns: [AtomicU64; UINTR_NUM_VECTORS],
+}
+
+impl UintrCounters {
+    pub const fn new() -> Self {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const ZERO: AtomicU64 = AtomicU64::new(0);
+        Self {
+            sent: ZERO,
+            posted: ZERO,
+            delivered: ZERO,
+            coalesced: ZERO,
+            total_latency_ns: ZERO,
+            max_latency_ns: ZERO,
+            post_time_ns: [ZERO; UINTR_NUM_VECTORS],
+        }
+    }
+
+    pub fn record_send(&self) {
+        self.sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a post of `vector`, which is coalesced if it is still pending.
+    ///
+    /// Must be called before the vector is marked pending, so that the post
+    /// time is set before it can be delivered.
+    pub fn record_post(&self, vector: usize, pending: bool) {
+        self.posted.fetch_add(1, Ordering::Relaxed);
+        if pending {
+            self.coalesced.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.post_time_ns[vector].store(get_time_ns(), Ordering::Relaxed);
+        }
+    }
+
+    /// Records a delivery of `vector` to the handler.
+    pub fn record_delivery(&self, vector: usize) {
+        let post_time = self.post_time_ns[vector].load(Ordering::Relaxed);
+        let latency = get_time_ns().saturating_sub(post_time);
+        self.delivered.fetch_add(1, Ordering::Relaxed);
+        self.total_latency_ns.fetch_add(latency, Ordering::Relaxed);
+        self.max_latency_ns.fetch_max(latency, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> UintrStats {
+        UintrStats {
+            sent: self.sent.load(Ordering::Relaxed),
+            posted: self.posted.load(Ordering::Relaxed),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+            total_latency_ns: self.total_latency_ns.load(Ordering::Relaxed),
+            max_latency_ns: self.max_latency_ns.load(Ordering::Relaxed),
+        }
+    }
+}
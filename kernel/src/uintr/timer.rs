@@ -0,0 +1,64 @@
+//! Per-task timers that post user interrupts to their receivers when they
+//! expire, like the user timers of Intel CPUs. They are checked on each timer
+//! interrupt, so they have the resolution of the timer interrupts.
+
+use alloc::{sync::Arc, vec::Vec};
+
+use super::{UintrReceiver, UintrVector};
+use crate::drivers::timer::get_time_ns;
+use crate::sync::Mutex;
+
+struct UintrTimer {
+    target: Arc<UintrVector>,
+    deadline_ns: u64,
+    /// Period of a periodic timer, or 0 for a one-shot timer.
+    interval_ns: u64,
+}
+
+/// Armed timers, at most one for each receiver.
+static TIMERS: Mutex<Vec<UintrTimer>> = Mutex::new(Vec::new());
+
+/// Arms the timer of the receiver of `target`, which posts a user interrupt
+/// to `target` after `delay_ns`, and then every `delay_ns` if `periodic`.
+/// The timer armed before is replaced, and is disarmed if `delay_ns` is 0.
+pub fn set_timer(target: Arc<UintrVector>, delay_ns: u64, periodic: bool) {
+    let mut timers = TIMERS.lock();
+    timers.retain(|t| !Arc::ptr_eq(t.target.receiver(), target.receiver()));
+    if delay_ns != 0 {
+        timers.push(UintrTimer {
+            target,
+            deadline_ns: get_time_ns() + delay_ns,
+            interval_ns: if periodic { delay_ns } else { 0 },
+        });
+    }
+}
+
+/// Disarms the timer of `receiver`, when it is unregistered.
+pub fn cancel_timer(receiver: &Arc<UintrReceiver>) {
+    TIMERS
+        .lock()
+        .retain(|t| !Arc::ptr_eq(t.target.receiver(), receiver));
+}
+
+/// Posts user interrupts for the expired timers, then drops the one-shot ones
+/// and those of unregistered receivers.
+///
+/// Called on each timer interrupt.
+pub fn expire_timers() {
+    let now = get_time_ns();
+    let mut timers = TIMERS.lock();
+    let mut i = 0;
+    while i < timers.len() {
+        let timer = &mut timers[i];
+        if timer.deadline_ns <= now {
+            if !timer.target.post() || timer.interval_ns == 0 {
+                timers.swap_remove(i);
+                continue;
+            }
+            // skip the periods missed, instead of posting them all at once
+            let missed = (now - timer.deadline_ns) / timer.interval_ns;
+            timer.deadline_ns += (missed + 1) * timer.interval_ns;
+        }
+        i += 1;
+    }
+}
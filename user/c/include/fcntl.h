@@ -0,0 +1,10 @@
+#ifndef __FCNTL_H__
+#define __FCNTL_H__
+
+#define O_RDONLY 00
+#define O_RDWR   02
+#define O_CREAT  0100
+#define O_EXCL   0200
+#define O_TRUNC  01000
+
+#endif // __FCNTL_H__
@@ -32,6 +32,8 @@ typedef uintptr_t size_t;
 typedef intptr_t ssize_t;
 
 typedef int pid_t;
+typedef intptr_t off_t;
+typedef unsigned int mode_t;
 
 #define NULL ((void *)0)
 
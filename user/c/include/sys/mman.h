@@ -0,0 +1,23 @@
+#ifndef __SYS_MMAN_H__
+#define __SYS_MMAN_H__
+
+#include <stddef.h>
+
+#define PROT_READ  0x1
+#define PROT_WRITE 0x2
+#define PROT_EXEC  0x4
+
+#define MAP_SHARED 0x01
+#define MAP_FIXED  0x10
+
+#define MAP_FAILED ((void *)-1)
+
+/* only MAP_SHARED mappings of shared memory objects are supported */
+void *mmap(void *addr, size_t length, int prot, int flags, int fd, off_t offset);
+/* only whole mappings made by mmap can be unmapped */
+int munmap(void *addr, size_t length);
+
+int shm_open(const char *name, int oflag, mode_t mode);
+int shm_unlink(const char *name);
+
+#endif // __SYS_MMAN_H__
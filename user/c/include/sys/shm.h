@@ -0,0 +1,36 @@
+#ifndef __SYS_SHM_H__
+#define __SYS_SHM_H__
+
+#include <stddef.h>
+
+typedef int key_t;
+
+#define IPC_PRIVATE ((key_t)0)
+
+#define IPC_CREAT 01000
+#define IPC_EXCL  02000
+
+#define IPC_RMID 0
+#define IPC_STAT 2
+
+#define SHM_RDONLY 010000
+#define SHM_RND    020000
+
+#define SHMLBA 4096
+
+struct shmid_ds {
+    key_t shm_key;
+    size_t shm_segsz;
+    size_t shm_cpid;
+    size_t shm_nattch;
+    /* times of the last attach and detach, in milliseconds */
+    size_t shm_atime;
+    size_t shm_dtime;
+};
+
+int shmget(key_t key, size_t size, int shmflg);
+void *shmat(int shmid, const void *shmaddr, int shmflg);
+int shmdt(const void *shmaddr);
+int shmctl(int shmid, int cmd, struct shmid_ds *buf);
+
+#endif // __SYS_SHM_H__
@@ -0,0 +1,31 @@
+#ifndef __UINTR_H__
+#define __UINTR_H__
+
+#include <stddef.h>
+
+/* User interrupt handler, which is called with the vector */
+typedef void (*uintr_handler_t)(unsigned long vector);
+
+int uintr_register_handler(void (*handler)(void), unsigned int flags);
+int uintr_unregister_handler(unsigned int flags);
+int uintr_vector_fd(unsigned long vector, unsigned int flags);
+int uintr_register_sender(int uintr_fd, unsigned int flags);
+int uintr_unregister_sender(int uintr_fd, unsigned int flags);
+int uintr_wait(unsigned int flags);
+
+/* Registers `handler` to be called through the entry stub, which saves the
+ * interrupted registers and returns with uiret */
+int uintr_register_receiver(uintr_handler_t handler, unsigned int flags);
+
+int uintr_senduipi(int uipi_index);
+int uintr_uiret(void);
+
+int uintr_register_irq(int irq, int uintr_fd, unsigned int flags);
+int uintr_irq_ack(int irq);
+
+/* Creates a revocable grant of the vector of `uintr_fd`, returns its fd, which
+ * senders can be registered with like a uintr fd */
+int uintr_grant(int uintr_fd, unsigned int flags);
+int uintr_revoke(int grant_fd, unsigned int flags);
+
+#endif // __UINTR_H__
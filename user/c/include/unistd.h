@@ -5,6 +5,8 @@
 
 ssize_t read(int, void *, size_t);
 ssize_t write(int, const void *, size_t);
+int close(int fd);
+int ftruncate(int fd, off_t length);
 
 pid_t getpid(void);
 int sched_yield(void);
@@ -0,0 +1,23 @@
+#include <sys/mman.h>
+
+#include "syscall.h"
+
+void *mmap(void *addr, size_t length, int prot, int flags, int fd, off_t offset)
+{
+    return (void *)syscall(SYS_mmap, addr, length, prot, flags, fd, offset);
+}
+
+int munmap(void *addr, size_t length)
+{
+    return syscall(SYS_munmap, addr, length);
+}
+
+int shm_open(const char *name, int oflag, mode_t mode)
+{
+    return syscall(SYS_shm_open, name, oflag, mode);
+}
+
+int shm_unlink(const char *name)
+{
+    return syscall(SYS_shm_unlink, name);
+}
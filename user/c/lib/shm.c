@@ -0,0 +1,23 @@
+#include <sys/shm.h>
+
+#include "syscall.h"
+
+int shmget(key_t key, size_t size, int shmflg)
+{
+    return syscall(SYS_shmget, key, size, shmflg);
+}
+
+void *shmat(int shmid, const void *shmaddr, int shmflg)
+{
+    return (void *)syscall(SYS_shmat, shmid, shmaddr, shmflg);
+}
+
+int shmdt(const void *shmaddr)
+{
+    return syscall(SYS_shmdt, shmaddr);
+}
+
+int shmctl(int shmid, int cmd, struct shmid_ds *buf)
+{
+    return syscall(SYS_shmctl, shmid, cmd, buf);
+}
@@ -13,6 +13,16 @@ ssize_t write(int fd, const void *buf, size_t count)
     return syscall(SYS_write, fd, buf, count);
 }
 
+int close(int fd)
+{
+    return syscall(SYS_close, fd);
+}
+
+int ftruncate(int fd, off_t length)
+{
+    return syscall(SYS_ftruncate, fd, length);
+}
+
 pid_t getpid(void)
 {
     return syscall(SYS_getpid);
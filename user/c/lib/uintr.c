@@ -0,0 +1,77 @@
+#include <uintr.h>
+
+#include "syscall.h"
+
+int uintr_register_handler(void (*handler)(void), unsigned int flags)
+{
+    return syscall(SYS_uintr_register_handler, handler, flags);
+}
+
+int uintr_unregister_handler(unsigned int flags)
+{
+    return syscall(SYS_uintr_unregister_handler, flags);
+}
+
+int uintr_vector_fd(unsigned long vector, unsigned int flags)
+{
+    return syscall(SYS_uintr_vector_fd, vector, flags);
+}
+
+int uintr_register_sender(int uintr_fd, unsigned int flags)
+{
+    return syscall(SYS_uintr_register_sender, uintr_fd, flags);
+}
+
+int uintr_unregister_sender(int uintr_fd, unsigned int flags)
+{
+    return syscall(SYS_uintr_unregister_sender, uintr_fd, flags);
+}
+
+int uintr_wait(unsigned int flags)
+{
+    return syscall(SYS_uintr_wait, flags);
+}
+
+/* Called by __uintr_entry */
+uintr_handler_t __uintr_handler;
+
+void __uintr_entry(void);
+
+int uintr_register_receiver(uintr_handler_t handler, unsigned int flags)
+{
+    int ret = uintr_register_handler(__uintr_entry, flags);
+    /* no user interrupts can be sent before there is a uintr fd */
+    if (ret == 0)
+        __uintr_handler = handler;
+    return ret;
+}
+
+int uintr_senduipi(int uipi_index)
+{
+    return syscall(SYS_uintr_notice, uipi_index);
+}
+
+int uintr_uiret(void)
+{
+    return syscall(SYS_uintr_uiret);
+}
+
+int uintr_register_irq(int irq, int uintr_fd, unsigned int flags)
+{
+    return syscall(SYS_uintr_register_irq, irq, uintr_fd, flags);
+}
+
+int uintr_irq_ack(int irq)
+{
+    return syscall(SYS_uintr_irq_ack, irq);
+}
+
+int uintr_grant(int uintr_fd, unsigned int flags)
+{
+    return syscall(SYS_uintr_grant, uintr_fd, flags);
+}
+
+int uintr_revoke(int grant_fd, unsigned int flags)
+{
+    return syscall(SYS_uintr_revoke, grant_fd, flags);
+}
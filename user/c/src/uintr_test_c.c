@@ -0,0 +1,54 @@
+#include <assert.h>
+#include <stdio.h>
+#include <sys/shm.h>
+#include <uintr.h>
+#include <unistd.h>
+
+#define SHM_LEN 256
+
+static volatile int handled = 0;
+
+static void uintr_handler(unsigned long vector)
+{
+    printf("Receiver process handles user interrupt %d.\n", (int)vector);
+    handled++;
+}
+
+int main()
+{
+    assert(uintr_register_receiver(uintr_handler, 0) == 0);
+    int uintr_fd = uintr_vector_fd(0, 0);
+    assert(uintr_fd >= 0);
+    int shmid = shmget(IPC_PRIVATE, SHM_LEN, 0);
+    unsigned char *addr = shmat(shmid, NULL, 0);
+
+    printf("Receiver process register handler ok! addr = %p\n", addr);
+
+    int pid = fork();
+    if (pid == 0) {
+        int index = uintr_register_sender(uintr_fd, 0);
+        assert(index >= 0);
+        unsigned char *addr = shmat(shmid, NULL, 0);
+        for (int i = 0; i < SHM_LEN; i++) {
+            addr[i] = (unsigned char)i;
+        }
+        printf("Sender process has finished writing. sender shm addr = %p\n", addr);
+
+        assert(uintr_senduipi(index) == 0);
+        return 0;
+    }
+
+    printf("Receiver process will wait for the user interrupt.\n");
+    assert(uintr_wait(0) == 0);
+    printf("Receiver process wakes up.\n");
+    assert(handled == 1);
+    for (int i = 0; i < SHM_LEN; i++) {
+        assert(addr[i] == (unsigned char)i);
+    }
+
+    int exit_code = 0;
+    assert(waitpid(pid, &exit_code) == pid);
+    assert(exit_code == 0);
+    printf("Multi process communicate ok! Receiver shm addr = %p\n", addr);
+    return 0;
+}
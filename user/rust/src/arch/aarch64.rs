@@ -1,6 +1,9 @@
 use core::arch::asm;
 
-use crate::syscall::{SYSCALL_CLONE, SYSCALL_EXIT};
+use crate::syscall::{
+    sys_uintr_clui, sys_uintr_stui, SYSCALL_CLONE, SYSCALL_EXIT, SYSCALL_UINTR_UIRET,
+};
+use crate::uintr::UINTR_HANDLER;
 
 pub fn syscall(id: usize, args: [usize; 3]) -> isize {
     let ret;
@@ -16,6 +19,23 @@ pub fn syscall(id: usize, args: [usize; 3]) -> isize {
     ret
 }
 
+pub fn syscall6(id: usize, args: [usize; 6]) -> isize {
+    let ret;
+    unsafe {
+        asm!(
+            "svc #0",
+            inlateout("x0") args[0] => ret,
+            in("x1") args[1],
+            in("x2") args[2],
+            in("x3") args[3],
+            in("x4") args[4],
+            in("x5") args[5],
+            in("x8") id,
+        );
+    }
+    ret
+}
+
 #[naked]
 #[allow(improper_ctypes_definitions)]
 pub extern "C" fn sys_clone(_entry: fn(usize) -> i32, _arg: usize, _newsp: usize) -> usize {
@@ -50,3 +70,98 @@ pub extern "C" fn sys_clone(_entry: fn(usize) -> i32, _arg: usize, _newsp: usize
         )
     }
 }
+
+/// Entry of all user interrupts, which calls the registered handler with the
+/// vector in x0.
+#[naked]
+pub unsafe extern "C" fn uintr_trampoline() -> ! {
+    asm!("
+        // save caller-saved registers
+        sub sp, sp, #160
+        stp x0, x1, [sp]
+        stp x2, x3, [sp, #16]
+        stp x4, x5, [sp, #32]
+        stp x6, x7, [sp, #48]
+        stp x8, x9, [sp, #64]
+        stp x10, x11, [sp, #80]
+        stp x12, x13, [sp, #96]
+        stp x14, x15, [sp, #112]
+        stp x16, x17, [sp, #128]
+        stp x18, x30, [sp, #144]
+
+        adrp x9, {handler}
+        ldr x9, [x9, :lo12:{handler}]
+        blr x9
+
+        ldp x0, x1, [sp]
+        ldp x2, x3, [sp, #16]
+        ldp x4, x5, [sp, #32]
+        ldp x6, x7, [sp, #48]
+        ldp x8, x9, [sp, #64]
+        ldp x10, x11, [sp, #80]
+        ldp x12, x13, [sp, #96]
+        ldp x14, x15, [sp, #112]
+        ldp x16, x17, [sp, #128]
+        ldp x18, x30, [sp, #144]
+        add sp, sp, #160
+
+        // x8 is restored again by uiret, with the whole interrupted context
+        mov x8, {uiret}
+        svc #0",
+        handler = sym UINTR_HANDLER,
+        uiret = const SYSCALL_UINTR_UIRET,
+        options(noreturn),
+    )
+}
+
+/// Sets the user interrupt flag, which is emulated by the kernel.
+pub fn stui() {
+    sys_uintr_stui();
+}
+
+/// Clears the user interrupt flag, which is emulated by the kernel.
+pub fn clui() {
+    sys_uintr_clui();
+}
+
+/// Saves the callee-saved registers of the current context on its stack and
+/// the stack pointer to `prev_sp`, then restores the context saved at
+/// `next_sp`.
+#[naked]
+pub unsafe extern "C" fn context_switch(_prev_sp: *mut usize, _next_sp: usize) {
+    asm!(
+        "
+        sub sp, sp, #96
+        stp x19, x20, [sp]
+        stp x21, x22, [sp, #16]
+        stp x23, x24, [sp, #32]
+        stp x25, x26, [sp, #48]
+        stp x27, x28, [sp, #64]
+        stp x29, x30, [sp, #80]
+        mov x9, sp
+        str x9, [x0]
+
+        mov sp, x1
+        ldp x19, x20, [sp]
+        ldp x21, x22, [sp, #16]
+        ldp x23, x24, [sp, #32]
+        ldp x25, x26, [sp, #48]
+        ldp x27, x28, [sp, #64]
+        ldp x29, x30, [sp, #80]
+        add sp, sp, #96
+        ret",
+        options(noreturn),
+    )
+}
+
+/// Builds a context below `stack_top` that enters `entry` when switched to,
+/// returns its stack pointer for [`context_switch`].
+pub fn init_context(stack_top: usize, entry: extern "C" fn() -> !) -> usize {
+    // x19-x28, x29 and x30 as the return address
+    let sp = (stack_top & !0xf) - 12 * 8;
+    unsafe {
+        core::ptr::write_bytes(sp as *mut usize, 0, 11);
+        *((sp + 11 * 8) as *mut usize) = entry as usize;
+    }
+    sp
+}
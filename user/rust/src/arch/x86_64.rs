@@ -1,6 +1,7 @@
 use core::arch::asm;
 
 use crate::syscall::{SYSCALL_CLONE, SYSCALL_EXIT};
+use crate::uintr::UINTR_HANDLER;
 
 pub fn syscall(id: usize, args: [usize; 3]) -> isize {
     let ret;
@@ -18,6 +19,25 @@ pub fn syscall(id: usize, args: [usize; 3]) -> isize {
     ret
 }
 
+pub fn syscall6(id: usize, args: [usize; 6]) -> isize {
+    let ret;
+    unsafe {
+        asm!(
+            "syscall",
+            inlateout("rax") id => ret,
+            in("rdi") args[0],
+            in("rsi") args[1],
+            in("rdx") args[2],
+            in("r10") args[3],
+            in("r8") args[4],
+            in("r9") args[5],
+            out("rcx") _,
+            out("r11") _,
+        );
+    }
+    ret
+}
+
 #[naked]
 #[allow(improper_ctypes_definitions)]
 pub extern "C" fn sys_clone(_entry: fn(usize) -> i32, _arg: usize, _newsp: usize) -> usize {
@@ -57,3 +77,96 @@ pub extern "C" fn sys_clone(_entry: fn(usize) -> i32, _arg: usize, _newsp: usize
         )
     }
 }
+
+/// Entry of all user interrupts, which calls the registered handler with the
+/// vector on the top of the stack.
+#[naked]
+pub unsafe extern "C" fn uintr_trampoline() -> ! {
+    asm!("
+        // save caller-saved registers, and rbp to realign the stack
+        push rax
+        push rcx
+        push rdx
+        push rsi
+        push rdi
+        push r8
+        push r9
+        push r10
+        push r11
+        push rbp
+        mov rbp, rsp
+
+        mov rdi, [rsp + 80]
+        and rsp, -16
+        call [rip + {handler}]
+
+        mov rsp, rbp
+        pop rbp
+        pop r11
+        pop r10
+        pop r9
+        pop r8
+        pop rdi
+        pop rsi
+        pop rdx
+        pop rcx
+        pop rax
+        // skip the vector
+        add rsp, 8
+        uiret",
+        handler = sym UINTR_HANDLER,
+        options(noreturn),
+    )
+}
+
+/// Sets the user interrupt flag, which is emulated by the kernel on CPUs
+/// without user interrupt support.
+pub fn stui() {
+    unsafe { asm!("stui") };
+}
+
+/// Clears the user interrupt flag, which is emulated by the kernel on CPUs
+/// without user interrupt support.
+pub fn clui() {
+    unsafe { asm!("clui") };
+}
+
+/// Saves the callee-saved registers of the current context on its stack and
+/// the stack pointer to `prev_sp`, then restores the context saved at
+/// `next_sp`.
+#[naked]
+pub unsafe extern "C" fn context_switch(_prev_sp: *mut usize, _next_sp: usize) {
+    asm!(
+        "
+        push rbp
+        push rbx
+        push r12
+        push r13
+        push r14
+        push r15
+        mov [rdi], rsp
+
+        mov rsp, rsi
+        pop r15
+        pop r14
+        pop r13
+        pop r12
+        pop rbx
+        pop rbp
+        ret",
+        options(noreturn),
+    )
+}
+
+/// Builds a context below `stack_top` that enters `entry` when switched to,
+/// returns its stack pointer for [`context_switch`].
+pub fn init_context(stack_top: usize, entry: extern "C" fn() -> !) -> usize {
+    // 6 callee-saved registers, the return address, then the stack is
+    // aligned as on a function entry
+    let sp = (stack_top & !0xf) - 8 * 8;
+    unsafe {
+        core::ptr::write_bytes(sp as *mut usize, 0, 6);
+        *((sp + 6 * 8) as *mut usize) = entry as usize;
+    }
+    sp
+}
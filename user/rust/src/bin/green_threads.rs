@@ -0,0 +1,58 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use user_lib::{get_time, green};
+
+const TICK_MS: usize = 10;
+const RUN_MS: isize = 1000;
+const NUM_THREADS: usize = 4;
+
+static STOP: AtomicBool = AtomicBool::new(false);
+static PROGRESS: [AtomicUsize; NUM_THREADS] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+
+/// CPU-bound, and never yields.
+fn spin(i: usize) -> i32 {
+    while !STOP.load(Ordering::Relaxed) {
+        PROGRESS[i].fetch_add(1, Ordering::Relaxed);
+    }
+    i as i32
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert!(green::init(TICK_MS));
+    let tids = [0, 1, 2, 3].map(|i| green::spawn(spin, i).unwrap());
+
+    // the main thread spins too, and is preempted as well
+    let start = get_time();
+    while get_time() - start < RUN_MS {}
+    STOP.store(true, Ordering::Relaxed);
+    for (i, tid) in tids.into_iter().enumerate() {
+        assert_eq!(green::join(tid), Some(i as i32));
+    }
+
+    let progress = [0, 1, 2, 3].map(|i| PROGRESS[i].load(Ordering::Relaxed));
+    let total: usize = progress.iter().sum();
+    for (i, &n) in progress.iter().enumerate() {
+        println!(
+            "Thread {}: {} iterations ({}%)",
+            i,
+            n,
+            n * 100 / total.max(1)
+        );
+    }
+    println!("{} preemptions in {} ms.", green::preemptions(), RUN_MS);
+    let (min, max) = (progress.iter().min(), progress.iter().max());
+    assert!(min.unwrap() * 4 >= *max.unwrap(), "unfair progress");
+    println!("green_threads passed!");
+    0
+}
@@ -4,7 +4,7 @@
 #[macro_use]
 extern crate user_lib;
 
-use user_lib::{exit, fork, IPC_PRIVATE, shmat, shmget, sleep, wait};
+use user_lib::{exit, fork, IPC_PRIVATE, shmat, shmdt, shmget, sleep, wait};
 
 const NUM: usize = 64;
 
@@ -26,6 +26,7 @@ pub fn main() -> i32 {
             unsafe {
                 *addr = *addr * 2;
             }
+            assert_eq!(shmdt(addr as usize), 0);
             exit(0);
         } else {
             let addr = shmat(shmid, 0, 0) as *mut isize;
@@ -47,6 +48,7 @@ pub fn main() -> i32 {
                     assert_eq!(exit_pid * 2, *pid_shmaddr_list[i].shmaddr);
                     cnt += 1;
                 }
+                assert_eq!(shmdt(pid_shmaddr_list[i].shmaddr as usize), 0);
                 break;
             }
         }
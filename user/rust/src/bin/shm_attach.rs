@@ -0,0 +1,55 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, shmat, shmctl, shmdt, shmget, waitpid};
+use user_lib::{IPC_PRIVATE, IPC_RMID, SHMLBA, SHM_RDONLY, SHM_RND};
+
+const SHM_SIZE: usize = 4096 * 2;
+const ADDR: usize = 0x10_0000_0000;
+const ADDR2: usize = 0x20_0000_0000;
+
+/// Attaches segments at chosen addresses, and read-only.
+#[no_mangle]
+pub fn main() -> i32 {
+    let shmid = shmget(IPC_PRIVATE, SHM_SIZE, 0);
+    assert!(shmid >= 0);
+    assert_eq!(shmat(shmid, ADDR, 0), ADDR as isize);
+    unsafe { (ADDR as *mut usize).write_volatile(42) };
+
+    // overlapping areas
+    assert_eq!(shmat(shmid, ADDR, 0), -1);
+    assert_eq!(shmat(shmid, ADDR + SHMLBA, 0), -1);
+    assert_eq!(shmat(shmid, ADDR - SHMLBA, 0), -1);
+    assert_eq!(shmat(shmid, main as usize, SHM_RND), -1);
+    assert_eq!(shmat(shmid, usize::MAX, SHM_RND), -1);
+    let next = ADDR + SHM_SIZE;
+    assert_eq!(shmat(shmid, next, 0), next as isize);
+    assert_eq!(unsafe { (next as *const usize).read_volatile() }, 42);
+
+    // unaligned addresses need SHM_RND
+    assert_eq!(shmat(shmid, ADDR2 + 1, 0), -1);
+    assert_eq!(shmat(shmid, ADDR2 + 1, SHM_RND), ADDR2 as isize);
+
+    let ro = shmat(shmid, 0, SHM_RDONLY) as usize;
+    assert_eq!(unsafe { (ro as *const usize).read_volatile() }, 42);
+    let pid = fork();
+    if pid == 0 {
+        // killed by the page fault
+        unsafe { (ro as *mut usize).write_volatile(0) };
+        exit(0);
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -1);
+    assert_eq!(unsafe { (ro as *const usize).read_volatile() }, 42);
+
+    for addr in [ADDR, next, ADDR2, ro] {
+        assert_eq!(shmdt(addr), 0);
+    }
+    assert_eq!(shmctl(shmid, IPC_RMID, None), 0);
+    println!("shm_attach passed!");
+    0
+}
@@ -0,0 +1,67 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{getpid, shmat, shmctl, shmdt, shmget, ShmidDs, IPC_PRIVATE, IPC_RMID, IPC_STAT};
+
+const SHM_SIZE: usize = 4096 * 3 + 100;
+/// The largest segments, which take more memory in total than the machine has.
+const BIG_SHM_SIZE: usize = 4096 * 256;
+const BIG_ROUNDS: usize = 256;
+
+fn stat(shmid: isize) -> Option<ShmidDs> {
+    let mut ds = ShmidDs::default();
+    match shmctl(shmid, IPC_STAT, Some(&mut ds)) {
+        0 => Some(ds),
+        _ => None,
+    }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let shmid = shmget(IPC_PRIVATE, SHM_SIZE, 0);
+    assert!(shmid >= 0);
+    let ds = stat(shmid).unwrap();
+    assert_eq!(ds.shm_key, IPC_PRIVATE);
+    assert_eq!(ds.shm_segsz, SHM_SIZE);
+    assert_eq!(ds.shm_cpid, getpid() as usize);
+    assert_eq!(ds.shm_nattch, 0);
+
+    let addr1 = shmat(shmid, 0, 0);
+    let addr2 = shmat(shmid, 0, 0);
+    let ds = stat(shmid).unwrap();
+    assert_eq!(ds.shm_nattch, 2);
+    assert!(ds.shm_atime > 0);
+
+    // marked for destruction, but kept until the last detach
+    assert_eq!(shmctl(shmid, IPC_RMID, None), 0);
+    assert_eq!(shmat(shmid, 0, 0), -1);
+    unsafe { (addr1 as *mut usize).write_volatile(42) };
+    assert_eq!(shmdt(addr1 as usize), 0);
+    let ds = stat(shmid).unwrap();
+    assert_eq!(ds.shm_nattch, 1);
+    assert!(ds.shm_dtime >= ds.shm_atime);
+    assert_eq!(unsafe { (addr2 as *const usize).read_volatile() }, 42);
+    assert_eq!(shmdt(addr2 as usize), 0);
+    assert!(stat(shmid).is_none());
+    assert_eq!(shmctl(shmid, IPC_RMID, None), -1);
+
+    assert_eq!(shmctl(shmid + 1000, IPC_RMID, None), -1);
+    let shmid = shmget(IPC_PRIVATE, SHM_SIZE, 0);
+    assert_eq!(shmctl(shmid, 1, None), -1); // unsupported command
+
+    // the frames are freed, or this runs out of memory
+    for _ in 0..BIG_ROUNDS {
+        let shmid = shmget(IPC_PRIVATE, BIG_SHM_SIZE, 0);
+        assert!(shmid >= 0);
+        let addr = shmat(shmid, 0, 0);
+        assert!(addr > 0);
+        assert_eq!(shmctl(shmid, IPC_RMID, None), 0);
+        assert_eq!(shmdt(addr as usize), 0);
+    }
+    assert_eq!(shmctl(shmid, IPC_RMID, None), 0);
+    println!("shm_ctl passed!");
+    0
+}
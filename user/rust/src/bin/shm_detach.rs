@@ -0,0 +1,56 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, shmat, shmdt, shmget, waitpid, IPC_PRIVATE};
+
+const SHM_SIZE: usize = 4096 * 2;
+const ROUNDS: usize = 300;
+
+/// Attaches and detaches a segment repeatedly, and checks that the data
+/// outlive the mappings.
+#[no_mangle]
+pub fn main() -> i32 {
+    let shmid = shmget(IPC_PRIVATE, SHM_SIZE, 0);
+    assert!(shmid >= 0);
+    let addr = shmat(shmid, 0, 0) as usize;
+    unsafe { (addr as *mut usize).write_volatile(42) };
+
+    // only the start address of an attached segment can be detached
+    assert_eq!(shmdt(addr + 4096), -1);
+    assert_eq!(shmdt(main as usize), -1);
+    assert_eq!(shmdt(0), -1);
+    assert_eq!(shmdt(usize::MAX), -1);
+    assert_eq!(shmdt(addr), 0);
+    assert_eq!(shmdt(addr), -1);
+
+    for i in 0..ROUNDS {
+        let addr = shmat(shmid, 0, 0);
+        assert!(addr > 0);
+        let value = unsafe { &mut *(addr as *mut usize) };
+        assert_eq!(*value, 42 + i);
+        *value += 1;
+        assert_eq!(shmdt(addr as usize), 0);
+    }
+
+    // the child detaches its own mapping only
+    let addr = shmat(shmid, 0, 0) as usize;
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(shmdt(addr), 0);
+        assert_eq!(shmdt(addr), -1);
+        exit(0);
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert_eq!(
+        unsafe { (addr as *const usize).read_volatile() },
+        42 + ROUNDS
+    );
+    assert_eq!(shmdt(addr), 0);
+    println!("shm_detach passed!");
+    0
+}
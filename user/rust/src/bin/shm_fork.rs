@@ -0,0 +1,75 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{exit, fork, sched_yield, shmat, shmctl, shmdt, shmget, waitpid, ShmidDs};
+use user_lib::{IPC_CREAT, IPC_EXCL, IPC_RMID, IPC_STAT};
+
+const KEY: usize = 0x666f_726b;
+const CHILDREN: usize = 16;
+const INIT: usize = 1000;
+
+#[repr(C)]
+struct Shared {
+    go: AtomicUsize,
+    counter: AtomicUsize,
+}
+
+fn nattch(shmid: isize) -> usize {
+    let mut ds = ShmidDs::default();
+    assert_eq!(shmctl(shmid, IPC_STAT, Some(&mut ds)), 0);
+    ds.shm_nattch
+}
+
+/// Creates the segment and forks the children, then exits before them.
+fn creator() -> ! {
+    let shmid = shmget(KEY, 4096, IPC_CREAT | IPC_EXCL);
+    assert!(shmid >= 0);
+    let shared = unsafe { &*(shmat(shmid, 0, 0) as *const Shared) };
+    shared.counter.store(INIT, Ordering::Release);
+    for i in 0..CHILDREN {
+        if fork() == 0 {
+            while shared.go.load(Ordering::Acquire) == 0 {
+                sched_yield();
+            }
+            shared.counter.fetch_add(1, Ordering::AcqRel);
+            exit(0);
+        }
+        assert_eq!(nattch(shmid), i + 2);
+    }
+    exit(0);
+}
+
+/// Forked children count as attaches, and keep the segment of the exited
+/// creator.
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        creator();
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+
+    let shmid = shmget(KEY, 4096, 0);
+    assert!(shmid >= 0);
+    assert_eq!(nattch(shmid), CHILDREN);
+    let shared = unsafe { &*(shmat(shmid, 0, 0) as *const Shared) };
+    assert_eq!(shared.counter.load(Ordering::Acquire), INIT);
+    shared.go.store(1, Ordering::Release);
+
+    // the children detach when they exit
+    while nattch(shmid) > 1 {
+        sched_yield();
+    }
+    assert_eq!(shared.counter.load(Ordering::Acquire), INIT + CHILDREN);
+    assert_eq!(shmctl(shmid, IPC_RMID, None), 0);
+    assert_eq!(shmdt(shared as *const _ as usize), 0);
+    assert_eq!(shmget(KEY, 4096, 0), -1);
+    println!("shm_fork passed!");
+    0
+}
@@ -0,0 +1,72 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{
+    exit, fork, shmat, shmctl, shmdt, shmget, waitpid, ShmidDs, IPC_CREAT, IPC_EXCL, IPC_PRIVATE,
+    IPC_RMID, IPC_STAT,
+};
+
+const KEY: usize = 0x6e69_6d62;
+const SHM_SIZE: usize = 4096;
+const MAGIC: usize = 0x5eed;
+
+/// Gets the segment of `KEY` and attaches it, creating it if it does not
+/// exist.
+fn attach_key() -> &'static AtomicUsize {
+    let shmid = shmget(KEY, SHM_SIZE, IPC_CREAT | 0o600);
+    assert!(shmid >= 0);
+    unsafe { &*(shmat(shmid, 0, 0) as *const AtomicUsize) }
+}
+
+/// Processes find the same segment by its key, without inheriting it.
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(shmget(KEY, SHM_SIZE, 0), -1); // not created yet
+
+    let pid = fork();
+    if pid == 0 {
+        attach_key().store(MAGIC, Ordering::Release);
+        exit(0);
+    }
+    let value = attach_key();
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert_eq!(value.load(Ordering::Acquire), MAGIC);
+    assert_eq!(shmdt(value as *const _ as usize), 0);
+
+    let shmid = shmget(KEY, SHM_SIZE, 0);
+    assert!(shmid >= 0);
+    assert_eq!(shmget(KEY, SHM_SIZE, IPC_CREAT), shmid);
+    assert_eq!(shmget(KEY, 1, 0), shmid);
+    assert_eq!(shmget(KEY, 0, 0), shmid);
+    assert_eq!(shmget(KEY, SHM_SIZE + 1, 0), -1); // larger than the segment
+    assert_eq!(shmget(KEY, SHM_SIZE, IPC_CREAT | IPC_EXCL), -1);
+    assert_eq!(shmget(KEY + 1, SHM_SIZE, IPC_EXCL), -1); // no IPC_CREAT
+    let mut ds = ShmidDs::default();
+    assert_eq!(shmctl(shmid, IPC_STAT, Some(&mut ds)), 0);
+    assert_eq!(ds.shm_key, KEY);
+
+    // private segments are always new
+    let private = shmget(IPC_PRIVATE, SHM_SIZE, IPC_CREAT | IPC_EXCL);
+    assert!(private >= 0 && private != shmid);
+    let private2 = shmget(IPC_PRIVATE, SHM_SIZE, 0);
+    assert!(private2 >= 0 && private2 != private);
+    assert_eq!(shmctl(private, IPC_RMID, None), 0);
+    assert_eq!(shmctl(private2, IPC_RMID, None), 0);
+
+    // the key is released when the segment is marked for destruction
+    assert_eq!(shmctl(shmid, IPC_RMID, None), 0);
+    assert_eq!(shmget(KEY, SHM_SIZE, 0), -1);
+    let new_shmid = shmget(KEY, SHM_SIZE, IPC_CREAT | IPC_EXCL);
+    assert!(new_shmid >= 0);
+    let value = unsafe { &*(shmat(new_shmid, 0, 0) as *const AtomicUsize) };
+    assert_eq!(value.load(Ordering::Acquire), 0);
+    assert_eq!(shmctl(new_shmid, IPC_RMID, None), 0);
+    println!("shm_key passed!");
+    0
+}
@@ -0,0 +1,124 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, exit, fork, ftruncate, mmap, munmap, shm_open, shm_unlink, shmdt, waitpid};
+use user_lib::{EBUSY, MAP_FIXED, MAP_SHARED, O_CREAT, O_EXCL, O_RDONLY, O_RDWR, O_TRUNC};
+use user_lib::{PROT_READ, PROT_WRITE};
+
+const NAME: &str = "/nimbos_shm\0";
+const NAME2: &str = "/nimbos_shm2\0";
+const SIZE: usize = 4096 * 3;
+const FIXED_ADDR: usize = 0x30_0000_0000;
+const MAGIC: usize = 0xcafe;
+const RW: usize = PROT_READ | PROT_WRITE;
+
+fn map(fd: usize, len: usize, prot: usize) -> isize {
+    mmap(0, len, prot, MAP_SHARED, fd, 0)
+}
+
+fn map_fixed(fd: usize, addr: usize) -> isize {
+    mmap(addr, 4096, RW, MAP_SHARED | MAP_FIXED, fd, 0)
+}
+
+/// Named objects are shared by processes mapping them, and outlive their
+/// names.
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(shm_open(NAME, O_RDWR, 0), -1); // not created yet
+    assert_eq!(shm_open("nimbos_shm\0", O_RDWR | O_CREAT, 0), -1);
+    assert_eq!(shm_open("/nimbos/shm\0", O_RDWR | O_CREAT, 0), -1);
+    let fd = shm_open(NAME, O_RDWR | O_CREAT | O_EXCL, 0o600);
+    assert!(fd >= 0);
+    let fd = fd as usize;
+    assert_eq!(shm_open(NAME, O_RDWR | O_CREAT | O_EXCL, 0o600), -1);
+
+    assert_eq!(map(fd, 4096, RW), -1); // empty
+    assert_eq!(ftruncate(fd, SIZE), 0);
+    let addr = map(fd, SIZE, RW);
+    assert!(addr > 0);
+    let value = unsafe { &mut *(addr as *mut usize) };
+    assert_eq!(*value, 0);
+    *value = 42;
+    assert_eq!(ftruncate(fd, 0), EBUSY); // mapped
+    assert_eq!(shmdt(addr as usize), -1); // not a System V segment
+
+    assert_eq!(map(fd, SIZE + 1, RW), -1);
+    assert_eq!(mmap(0, SIZE, RW, MAP_SHARED, fd, 4096), -1);
+    assert_eq!(mmap(0, SIZE, RW, 0, fd, 0), -1);
+    assert_eq!(map(0, SIZE, RW), -1); // not a shared memory object
+    assert_eq!(map_fixed(fd, FIXED_ADDR), FIXED_ADDR as isize);
+    assert_eq!(unsafe { (FIXED_ADDR as *const usize).read_volatile() }, 42);
+    assert_eq!(map_fixed(fd, FIXED_ADDR), -1); // overlapped
+    assert_eq!(map_fixed(fd, addr as usize), -1);
+    assert_eq!(map_fixed(fd, FIXED_ADDR + 1), -1);
+
+    // read-only file descriptors can only be mapped read-only
+    let ro_fd = shm_open(NAME, O_RDONLY, 0) as usize;
+    assert_eq!(map(ro_fd, SIZE, RW), -1);
+    assert_eq!(ftruncate(ro_fd, SIZE), -1);
+    let ro = map(ro_fd, SIZE, PROT_READ);
+    assert_eq!(unsafe { (ro as *const usize).read_volatile() }, 42);
+    assert_eq!(shm_open(NAME, O_RDONLY | O_TRUNC, 0), -1);
+
+    // another process opens the object by name
+    let pid = fork();
+    if pid == 0 {
+        let fd = shm_open(NAME, O_RDWR, 0);
+        assert!(fd >= 0);
+        let addr = map(fd as usize, SIZE, RW);
+        assert!(addr > 0);
+        unsafe { ((addr as usize + SIZE - 8) as *mut usize).write_volatile(MAGIC) };
+        exit(0);
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    let last = (addr as usize + SIZE - 8) as *const usize;
+    assert_eq!(unsafe { last.read_volatile() }, MAGIC);
+
+    // the object is kept after it is unlinked and closed
+    assert_eq!(close(fd), 0);
+    assert_eq!(close(fd), -1);
+    assert_eq!(close(ro_fd), 0);
+    assert_eq!(shm_unlink(NAME), 0);
+    assert_eq!(shm_unlink(NAME), -1);
+    assert_eq!(shm_open(NAME, O_RDWR, 0), -1);
+    assert_eq!(unsafe { last.read_volatile() }, MAGIC);
+
+    // objects are truncated when reopened with O_TRUNC
+    let fd = shm_open(NAME2, O_RDWR | O_CREAT, 0o600) as usize;
+    assert_eq!(ftruncate(fd, 100), 0);
+    let fd2 = shm_open(NAME2, O_RDWR | O_TRUNC, 0) as usize;
+    assert_eq!(map(fd2, 100, RW), -1);
+
+    // objects grow while they are mapped, but only shrink below the pages
+    // mapped once they are unmapped
+    assert_eq!(ftruncate(fd, 4096), 0);
+    let addr = map(fd, 4096, RW);
+    assert!(addr > 0);
+    unsafe { (addr as *mut usize).write_volatile(MAGIC) };
+    assert_eq!(ftruncate(fd, SIZE), 0);
+    let grown = map(fd, SIZE, RW);
+    assert!(grown > 0);
+    assert_eq!(unsafe { (grown as *const usize).read_volatile() }, MAGIC);
+    let grown_last = (grown as usize + SIZE - 8) as *const usize;
+    assert_eq!(unsafe { grown_last.read_volatile() }, 0);
+    assert_eq!(ftruncate(fd, 4096), EBUSY);
+    assert_eq!(munmap(grown as usize, SIZE), 0);
+    assert_eq!(ftruncate(fd, 100), 0); // within the page still mapped
+    assert_eq!(unsafe { (addr as *const usize).read_volatile() }, MAGIC);
+    assert_eq!(ftruncate(fd, 0), EBUSY);
+    assert_eq!(munmap(addr as usize, SIZE), -1); // not the whole mapping
+    assert_eq!(munmap(addr as usize + 4096, 4096), -1);
+    assert_eq!(munmap(addr as usize, 100), 0);
+    assert_eq!(munmap(addr as usize, 4096), -1); // already
+    assert_eq!(ftruncate(fd, 0), 0);
+    assert_eq!(shm_unlink(NAME2), 0);
+    assert_eq!(close(fd), 0);
+    assert_eq!(close(fd2), 0);
+    println!("shm_open passed!");
+    0
+}
@@ -0,0 +1,49 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, waitpid, ChannelReceiver, ChannelSender, ShmRing, UintrReceiver};
+
+const MESSAGES: u64 = 1000;
+const CAPACITY: usize = 4;
+const RINGS: usize = 100;
+
+extern "C" fn uintr_handler(vector: u64) {
+    assert_eq!(vector, 0);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let receiver = UintrReceiver::register(uintr_handler).unwrap();
+    let uintr_fd = receiver.vector_fd(0).unwrap();
+
+    // the ring is smaller than the messages, so that the producer fills it
+    let ring = ShmRing::<u64>::new(CAPACITY).unwrap();
+    let pid = fork();
+    if pid == 0 {
+        let sender = ChannelSender::new(&ring, &uintr_fd).unwrap();
+        for i in 0..MESSAGES {
+            sender.send(i);
+        }
+        exit(0);
+    }
+    let chan = ChannelReceiver::new(&ring, &receiver);
+    for i in 0..MESSAGES {
+        assert_eq!(chan.recv(), i);
+    }
+    assert!(chan.try_recv().is_none());
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+
+    // rings are detached on drop, so creating them repeatedly does not leak
+    for _ in 0..RINGS {
+        let ring = ShmRing::<u64>::new(CAPACITY).unwrap();
+        assert!(ring.try_push(1).is_ok());
+        assert_eq!(ring.try_pop(), Some(1));
+    }
+    println!("uintr_channel passed!");
+    0
+}
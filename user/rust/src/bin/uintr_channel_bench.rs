@@ -0,0 +1,99 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{
+    exit, fork, get_time, sched_yield, waitpid, ChannelReceiver, ChannelSender, ShmRing,
+    UintrReceiver,
+};
+
+const MESSAGES: u64 = 100_000;
+const CAPACITY: usize = 256;
+
+static DOORBELLS: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn uintr_handler(vector: u64) {
+    assert_eq!(vector, 0);
+    DOORBELLS.fetch_add(1, Ordering::Relaxed);
+}
+
+fn wait_child(pid: isize) {
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+}
+
+fn report(name: &str, elapsed_ms: isize) {
+    let elapsed_ms = elapsed_ms.max(1) as u64;
+    println!(
+        "{}: {} messages in {} ms, {} messages/s",
+        name,
+        MESSAGES,
+        elapsed_ms,
+        MESSAGES * 1000 / elapsed_ms
+    );
+}
+
+/// The consumer blocks on the doorbell when the ring is empty.
+fn bench_doorbell(receiver: &UintrReceiver) {
+    let uintr_fd = receiver.vector_fd(0).unwrap();
+    let ring = ShmRing::<u64>::new(CAPACITY).unwrap();
+    let start = get_time();
+    let pid = fork();
+    if pid == 0 {
+        let sender = ChannelSender::new(&ring, &uintr_fd).unwrap();
+        for i in 0..MESSAGES {
+            sender.send(i);
+        }
+        exit(0);
+    }
+    let chan = ChannelReceiver::new(&ring, receiver);
+    for i in 0..MESSAGES {
+        assert_eq!(chan.recv(), i);
+    }
+    report("doorbell", get_time() - start);
+    println!(
+        "The doorbell was rung {} times.",
+        DOORBELLS.load(Ordering::Relaxed)
+    );
+    wait_child(pid);
+}
+
+/// Both ends poll the ring, and yield the CPU when they can not proceed.
+fn bench_polling() {
+    let ring = ShmRing::<u64>::new(CAPACITY).unwrap();
+    let start = get_time();
+    let pid = fork();
+    if pid == 0 {
+        for i in 0..MESSAGES {
+            while ring.try_push(i).is_err() {
+                sched_yield();
+            }
+        }
+        exit(0);
+    }
+    for i in 0..MESSAGES {
+        let value = loop {
+            match ring.try_pop() {
+                Some(value) => break value,
+                None => sched_yield(),
+            };
+        };
+        assert_eq!(value, i);
+    }
+    report("polling", get_time() - start);
+    wait_child(pid);
+}
+
+/// Compares the throughput of the shared memory channel with the uintr
+/// doorbell against polling only.
+#[no_mangle]
+pub fn main() -> i32 {
+    let receiver = UintrReceiver::register(uintr_handler).unwrap();
+    bench_doorbell(&receiver);
+    bench_polling();
+    0
+}
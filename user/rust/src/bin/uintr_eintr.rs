@@ -0,0 +1,76 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{
+    exit, fork, get_time, read, sleep, waitpid, UintrFd, UintrReceiver, UintrSender, EINTR,
+};
+
+const SEND_DELAY_MS: usize = 50;
+const SLEEP_MS: usize = 5000;
+const STDIN: usize = 0;
+
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn uintr_handler(vector: u64) {
+    assert_eq!(vector, 0);
+    HANDLED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Forks a child which sends a user interrupt after a while, then sleeps
+/// another while before it exits.
+fn fork_sender(uintr_fd: &UintrFd) -> isize {
+    let pid = fork();
+    if pid == 0 {
+        let sender = UintrSender::register(uintr_fd).unwrap();
+        sleep(SEND_DELAY_MS);
+        assert!(sender.send());
+        sleep(SEND_DELAY_MS);
+        exit(0);
+    }
+    pid
+}
+
+/// Runs the waiting `f` while a user interrupt is posted, checks that it is
+/// cut short with `EINTR` after the handler has run.
+fn check_interrupted(name: &str, uintr_fd: &UintrFd, f: impl FnOnce() -> isize) {
+    let pid = fork_sender(uintr_fd);
+    let handled = HANDLED.load(Ordering::Relaxed);
+    let start = get_time();
+    assert_eq!(f(), EINTR);
+    let elapsed = get_time() - start;
+    assert_eq!(HANDLED.load(Ordering::Relaxed), handled + 1);
+    assert!(elapsed < SLEEP_MS as isize);
+    println!("{} interrupted after {}ms.", name, elapsed);
+
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let receiver = UintrReceiver::register(uintr_handler).unwrap();
+    let uintr_fd = receiver.vector_fd(0).unwrap();
+
+    check_interrupted("sleep", &uintr_fd, || sleep(SLEEP_MS));
+    check_interrupted("read", &uintr_fd, || {
+        let mut c = [0u8; 1];
+        read(STDIN, &mut c)
+    });
+
+    // the child sleeps longer than the interrupt delay
+    let pid = fork_sender(&uintr_fd);
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), EINTR);
+    assert_eq!(HANDLED.load(Ordering::Relaxed), 3);
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    println!("waitpid interrupted.");
+
+    println!("uintr_eintr passed!");
+    0
+}
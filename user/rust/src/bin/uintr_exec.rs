@@ -0,0 +1,30 @@
+#![no_std]
+#![no_main]
+
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{exec, UintrReceiver, UintrSender};
+
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn uintr_handler(vector: u64) {
+    assert_eq!(vector, 0);
+    HANDLED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Registers as both a receiver and a sender, then checks in
+/// `uintr_exec_child` that all registrations are dropped by `exec`.
+#[no_mangle]
+pub fn main() -> i32 {
+    let receiver = UintrReceiver::register(uintr_handler).unwrap();
+    let uintr_fd = receiver.vector_fd(0).unwrap();
+    assert_eq!(uintr_fd.as_raw(), 3);
+    let sender = UintrSender::register(&uintr_fd).unwrap();
+    assert_eq!(sender.index(), 0);
+    assert!(sender.send());
+    assert_eq!(HANDLED.load(Ordering::Relaxed), 1);
+
+    exec("uintr_exec_child\0");
+    panic!("unreachable!");
+}
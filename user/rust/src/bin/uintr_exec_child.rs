@@ -0,0 +1,28 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    close, uintr_notice, uintr_register_handler, uintr_register_sender, uintr_vector_fd,
+};
+
+/// Executed by `uintr_exec`, with the uintr file descriptor 3 and the UITT
+/// entry 0 before `exec`.
+#[no_mangle]
+pub fn main() -> i32 {
+    // the UITT entry is dropped
+    assert_eq!(uintr_notice(0), -1);
+    // no longer a receiver
+    assert_eq!(uintr_vector_fd(0, 0), -1);
+    // the file descriptor is kept, but its receiver is gone
+    let index = uintr_register_sender(3, 0);
+    assert!(index >= 0);
+    assert_eq!(uintr_notice(index as usize), -1);
+    assert_eq!(close(3), 0);
+    // can be registered again, with a new handler
+    assert_eq!(uintr_register_handler(main as usize, 0), 0);
+    println!("uintr_exec passed!");
+    0
+}
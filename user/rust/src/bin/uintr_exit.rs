@@ -0,0 +1,74 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{
+    exit, fork, shmat, shmget, sleep, waitpid, UintrFd, UintrReceiver, UintrSender, IPC_PRIVATE,
+};
+
+const SLEEP_MS: usize = 10;
+const MAX_RETRIES: usize = 100;
+
+/// Results of the sender in the shared memory.
+const RESULT_PENDING: usize = 0;
+const RESULT_SEND_FAILED: usize = 1;
+const RESULT_SEND_OK: usize = 2;
+
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn uintr_handler(vector: u64) {
+    assert_eq!(vector, 0);
+    HANDLED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The receiver exits after its first user interrupt, then notices from its
+/// sender fail.
+fn sender(uintr_fd: &UintrFd, result: &AtomicUsize) -> ! {
+    let sender = UintrSender::register(uintr_fd).unwrap();
+    assert!(sender.send());
+    let mut retries = 0;
+    while sender.send() && retries < MAX_RETRIES {
+        sleep(SLEEP_MS);
+        retries += 1;
+    }
+    let res = if sender.send() {
+        RESULT_SEND_OK
+    } else {
+        RESULT_SEND_FAILED
+    };
+    result.store(res, Ordering::Release);
+    exit(0);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let shmid = shmget(IPC_PRIVATE, 4096, 0);
+    let result = unsafe { &*(shmat(shmid, 0, 0) as *const AtomicUsize) };
+    result.store(RESULT_PENDING, Ordering::Release);
+
+    let pid = fork();
+    if pid == 0 {
+        let receiver = UintrReceiver::register(uintr_handler).unwrap();
+        let uintr_fd = receiver.vector_fd(0).unwrap();
+        if fork() == 0 {
+            sender(&uintr_fd, result);
+        }
+        receiver.wait();
+        assert_eq!(HANDLED.load(Ordering::Relaxed), 1);
+        exit(0);
+    }
+
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    // the sender is not our child, wait for its result in the shared memory
+    while result.load(Ordering::Acquire) == RESULT_PENDING {
+        sleep(SLEEP_MS);
+    }
+    assert_eq!(result.load(Ordering::Acquire), RESULT_SEND_FAILED);
+    println!("uintr_exit passed!");
+    0
+}
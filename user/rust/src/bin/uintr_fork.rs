@@ -0,0 +1,44 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{exit, fork, uintr_wait, waitpid, UintrReceiver, UintrSender};
+
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn uintr_handler(vector: u64) {
+    assert_eq!(vector, 0);
+    HANDLED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The child of `fork` inherits the uintr file descriptors, but neither the
+/// receiver nor the sender registrations.
+#[no_mangle]
+pub fn main() -> i32 {
+    let receiver = UintrReceiver::register(uintr_handler).unwrap();
+    let uintr_fd = receiver.vector_fd(0).unwrap();
+    let sender = UintrSender::register(&uintr_fd).unwrap();
+    assert!(sender.send());
+    assert_eq!(HANDLED.load(Ordering::Relaxed), 1);
+
+    let pid = fork();
+    if pid == 0 {
+        assert!(!sender.send());
+        assert!(receiver.vector_fd(1).is_none());
+        assert_eq!(uintr_wait(0), -1);
+        let sender = UintrSender::register(&uintr_fd).unwrap();
+        assert!(sender.send());
+        exit(0);
+    }
+
+    receiver.wait();
+    assert_eq!(HANDLED.load(Ordering::Relaxed), 2);
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    println!("uintr_fork passed!");
+    0
+}
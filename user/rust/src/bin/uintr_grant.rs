@@ -0,0 +1,89 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{
+    exit, fork, sched_yield, shmat, shmget, uintr_revoke, waitpid, UintrGrant, UintrReceiver,
+    UintrSender, IPC_PRIVATE,
+};
+
+/// Steps of the test in the shared memory.
+const STEP_START: usize = 0;
+const STEP_SENT: usize = 1;
+const STEP_REVOKED: usize = 2;
+const STEP_DONE: usize = 3;
+
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn uintr_handler(vector: u64) {
+    assert_eq!(vector, 0);
+    HANDLED.fetch_add(1, Ordering::Relaxed);
+}
+
+fn wait_step(step: &AtomicUsize, expected: usize) {
+    while step.load(Ordering::Acquire) != expected {
+        sched_yield();
+    }
+}
+
+/// Sends with the inherited grant until it is revoked.
+fn sender(step: &AtomicUsize, grant: &UintrGrant) -> ! {
+    let sender = UintrSender::register_grant(grant).unwrap();
+    assert!(UintrSender::register_grant(grant).is_none()); // already
+    assert_eq!(uintr_revoke(grant.as_raw(), 0), -1); // not the receiver
+    assert!(sender.send());
+    step.store(STEP_SENT, Ordering::Release);
+
+    wait_step(step, STEP_REVOKED);
+    assert!(!sender.send());
+    drop(sender);
+    assert!(UintrSender::register_grant(grant).is_none());
+    step.store(STEP_DONE, Ordering::Release);
+    exit(0);
+}
+
+/// A grant is a file descriptor passed to forked children like a uintr file
+/// descriptor, which the receiver can revoke.
+#[no_mangle]
+pub fn main() -> i32 {
+    let shmid = shmget(IPC_PRIVATE, 4096, 0);
+    let step = unsafe { &*(shmat(shmid, 0, 0) as *const AtomicUsize) };
+    step.store(STEP_START, Ordering::Release);
+
+    let receiver = UintrReceiver::register(uintr_handler).unwrap();
+    let uintr_fd = receiver.vector_fd(0).unwrap();
+    let grant = receiver.grant(&uintr_fd).unwrap();
+    let self_grant = receiver.grant(&uintr_fd).unwrap();
+    // only the grants are passed to the child
+    drop(uintr_fd);
+    let pid = fork();
+    if pid == 0 {
+        sender(step, &grant);
+    }
+
+    let self_sender = UintrSender::register_grant(&self_grant).unwrap();
+    assert!(self_sender.send());
+    assert_eq!(HANDLED.load(Ordering::Relaxed), 1);
+
+    while HANDLED.load(Ordering::Relaxed) < 2 {
+        receiver.wait();
+    }
+    wait_step(step, STEP_SENT);
+    assert!(receiver.revoke(&grant));
+    assert!(!receiver.revoke(&grant)); // already
+    step.store(STEP_REVOKED, Ordering::Release);
+    wait_step(step, STEP_DONE);
+
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    // nothing from the revoked grant, but the other one still works
+    assert_eq!(HANDLED.load(Ordering::Relaxed), 2);
+    assert!(self_sender.send());
+    assert_eq!(HANDLED.load(Ordering::Relaxed), 3);
+    println!("uintr_grant passed!");
+    0
+}
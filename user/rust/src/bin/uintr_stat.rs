@@ -0,0 +1,32 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{getpid, UintrStats};
+
+/// Prints the user interrupt statistics of all the tasks, which have pids up
+/// to ours.
+#[no_mangle]
+pub fn main() -> i32 {
+    println!(
+        "{:>4} {:>8} {:>8} {:>8} {:>8} {:>12} {:>12}",
+        "PID", "SENT", "POSTED", "DELIV", "COALESC", "AVG_LAT(ns)", "MAX_LAT(ns)"
+    );
+    for pid in 1..=getpid() as usize {
+        if let Some(stats) = UintrStats::get(pid) {
+            println!(
+                "{:>4} {:>8} {:>8} {:>8} {:>8} {:>12} {:>12}",
+                pid,
+                stats.sent,
+                stats.posted,
+                stats.delivered,
+                stats.coalesced,
+                stats.avg_latency_ns(),
+                stats.max_latency_ns
+            );
+        }
+    }
+    0
+}
@@ -0,0 +1,87 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{exit, fork, sleep, waitpid, UintrReceiver, UintrSender, UintrStats};
+
+const SLEEP_MS: usize = 10;
+const CHILD_SENDS: usize = 3;
+
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+/// UITT index of the self-send in the handler.
+static INDEX: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn uintr_handler(vector: u64) {
+    assert_eq!(vector, 0);
+    if HANDLED.fetch_add(1, Ordering::Relaxed) == 0 {
+        // handlers are not nested, so the second one is coalesced
+        let index = INDEX.load(Ordering::Relaxed);
+        assert_eq!(user_lib::uintr_notice(index), 0);
+        assert_eq!(user_lib::uintr_notice(index), 0);
+    }
+}
+
+/// Checks the receiver side of `stats`, after `handled` user interrupts.
+///
+/// With the hardware backend, deliveries are not counted, and neither are
+/// coalesced posts, since pending vectors are moved out of the UPID by the CPU.
+fn check_received(stats: &UintrStats, handled: usize) {
+    if stats.delivered == 0 {
+        assert_eq!(stats.total_latency_ns, 0);
+        return;
+    }
+    assert_eq!(stats.delivered, handled as u64);
+    assert_eq!(stats.delivered + stats.coalesced, stats.posted);
+    assert!(stats.max_latency_ns <= stats.total_latency_ns);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let empty = UintrStats::get(0).unwrap();
+    assert_eq!(empty.sent + empty.posted + empty.delivered, 0);
+
+    let receiver = UintrReceiver::register(uintr_handler).unwrap();
+    let uintr_fd = receiver.vector_fd(0).unwrap();
+    let sender = UintrSender::register(&uintr_fd).unwrap();
+    INDEX.store(sender.index(), Ordering::Relaxed);
+
+    assert!(sender.send());
+    assert_eq!(HANDLED.load(Ordering::Relaxed), 2);
+    let stats = UintrStats::get(0).unwrap();
+    println!("Self-sends: {:?}", stats);
+    assert_eq!(stats.sent, 3);
+    assert_eq!(stats.posted, 3);
+    check_received(&stats, 2);
+
+    // The statistics of a child can be read until it is waited.
+    let pid = fork();
+    if pid == 0 {
+        let sender = UintrSender::register(&uintr_fd).unwrap();
+        for _ in 0..CHILD_SENDS {
+            assert!(sender.send());
+            sleep(SLEEP_MS);
+        }
+        exit(0);
+    }
+    let posted = 3 + CHILD_SENDS as u64;
+    while UintrStats::get(0).unwrap().posted < posted {
+        receiver.wait();
+    }
+    let child_stats = UintrStats::get(pid as usize).unwrap();
+    assert_eq!(child_stats.sent, CHILD_SENDS as u64);
+    assert_eq!(child_stats.posted, 0);
+    let stats = UintrStats::get(0).unwrap();
+    println!("With a child sender: {:?}", stats);
+    assert_eq!(stats.posted, posted);
+    check_received(&stats, HANDLED.load(Ordering::Relaxed));
+
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert!(UintrStats::get(pid as usize).is_none());
+    println!("uintr_stats passed!");
+    0
+}
@@ -3,25 +3,31 @@
 
 #[macro_use]
 extern crate user_lib;
-use user_lib::{exit, fork, IPC_PRIVATE, shmat, shmget, sleep, uintr_register_link, uintr_register_sender};
 
-#[no_mangle]
-pub fn main() -> i32 {
-    // TODO: register handler
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{exit, fork, shmat, shmget, UintrReceiver, UintrSender, IPC_PRIVATE};
 
-    let mut shmid = 0;
-    let link_id = uintr_register_link(0, &shmid);
-    let addr = shmat(shmid as isize, 0, 0);
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
 
-    println!("Receiver process register link ok! addr = {}", addr);
+extern "C" fn uintr_handler(vector: u64) {
+    println!("Receiver process handles user interrupt {}.", vector);
+    HANDLED.fetch_add(1, Ordering::Relaxed);
+}
 
+#[no_mangle]
+pub fn main() -> i32 {
+    let receiver = UintrReceiver::register(uintr_handler).unwrap();
+    let uintr_fd = receiver.vector_fd(0).unwrap();
     let len = 256;
+    let shmid = shmget(IPC_PRIVATE, len, 0);
+    let addr = shmat(shmid, 0, 0);
+
+    println!("Receiver process register handler ok! addr = {}", addr);
 
     let pid = fork();
     if pid == 0 {
-        let mut shmid = 0;
-        let index = uintr_register_sender(link_id as usize, &shmid);
-        let addr = shmat(shmid as isize, 0, 0);
+        let sender = UintrSender::register(&uintr_fd).unwrap();
+        let addr = shmat(shmid, 0, 0);
         let start = addr as usize;
         for i in start..(start + len) {
             let addr: *mut u8 = i as *mut u8;
@@ -29,18 +35,22 @@ pub fn main() -> i32 {
                 *addr = i as u8;
             }
         }
-        println!("Sender process has finished writing. sender shm addr = {}", addr);
+        println!(
+            "Sender process has finished writing. sender shm addr = {}",
+            addr
+        );
 
-        //TODO: notice(senduipi)
+        assert!(sender.send());
 
         exit(0);
     } else {
-        shmat(shmid as isize, 0, 0);  // No-op; try to make parent and child addr different
-        let addr = shmat(shmid as isize, 0, 0);
+        shmat(shmid, 0, 0); // No-op; try to make parent and child addr different
+        let addr = shmat(shmid, 0, 0);
         let start = addr as usize;
-        println!("Receiver process will sleep for 100ms.");
-        sleep(100);
+        println!("Receiver process will wait for the user interrupt.");
+        receiver.wait();
         println!("Receiver process wakes up.");
+        assert_eq!(HANDLED.load(Ordering::Relaxed), 1);
         for i in start..(start + len) {
             let addr: *mut u8 = i as *mut u8;
             unsafe {
@@ -0,0 +1,129 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use user_lib::{
+    sched_yield, thread_spawn, uintr_notice, waitpid, UintrFd, UintrReceiver, UintrSender,
+};
+
+const NUM_WORKERS: usize = 3;
+const JOBS_PER_WORKER: usize = 4;
+/// Exits the worker instead of being added up.
+const STOP: usize = usize::MAX;
+
+/// Workers are woken by vectors of their own indices.
+static WAKEUPS: [AtomicUsize; NUM_WORKERS] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+static UINTR_FDS: [AtomicUsize; NUM_WORKERS] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+/// UITT indices registered by the main thread, and used by all the threads.
+static INDICES: [AtomicUsize; NUM_WORKERS] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+static JOBS: [AtomicUsize; NUM_WORKERS] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+static SUM: AtomicUsize = AtomicUsize::new(0);
+static READY: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn uintr_handler(vector: u64) {
+    WAKEUPS[vector as usize].fetch_add(1, Ordering::Relaxed);
+}
+
+/// Each worker is a receiver of its own, and handles the job posted to it
+/// on each wakeup.
+fn worker(i: usize) -> i32 {
+    let receiver = UintrReceiver::register(uintr_handler).unwrap();
+    let uintr_fd = receiver.vector_fd(i).unwrap();
+    UINTR_FDS[i].store(uintr_fd.as_raw(), Ordering::Release);
+    while !READY.load(Ordering::Acquire) {
+        sched_yield();
+    }
+
+    let mut handled = 0;
+    loop {
+        while WAKEUPS[i].load(Ordering::Relaxed) == handled {
+            // The wakeup would be handled before waiting for it, if it were
+            // posted after the count is checked.
+            receiver.disable();
+            if WAKEUPS[i].load(Ordering::Relaxed) == handled {
+                receiver.wait();
+            }
+            receiver.enable();
+        }
+        handled += 1;
+        match JOBS[i].swap(0, Ordering::AcqRel) {
+            STOP => break,
+            job => SUM.fetch_add(job, Ordering::Relaxed),
+        };
+        // the sender table is shared, so wake up the next worker with the
+        // index registered by the main thread
+        if i + 1 < NUM_WORKERS {
+            post_job(i + 1, 1);
+        }
+    }
+    core::mem::forget(uintr_fd); // closed by the main thread
+    handled as i32
+}
+
+/// Posts `job` to worker `i`, and waits for it to be taken.
+fn post_job(i: usize, job: usize) {
+    JOBS[i].store(job, Ordering::Release);
+    assert_eq!(uintr_notice(INDICES[i].load(Ordering::Relaxed)), 0);
+    while JOBS[i].load(Ordering::Acquire) != 0 {
+        sched_yield();
+    }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let tids = [0, 1, 2].map(|i| thread_spawn(worker, i));
+    for fd in &UINTR_FDS {
+        while fd.load(Ordering::Acquire) == 0 {
+            sched_yield();
+        }
+    }
+    let fds = [0, 1, 2].map(|i| UintrFd::from_raw(UINTR_FDS[i].load(Ordering::Relaxed)));
+    let senders = [0, 1, 2].map(|i| UintrSender::register(&fds[i]).unwrap());
+    for (index, sender) in INDICES.iter().zip(&senders) {
+        index.store(sender.index(), Ordering::Relaxed);
+    }
+    READY.store(true, Ordering::Release);
+
+    // Jobs posted to worker 0 are passed on to the others by themselves.
+    for job in 1..=JOBS_PER_WORKER {
+        post_job(0, job);
+    }
+    let expected = (1..=JOBS_PER_WORKER).sum::<usize>() + JOBS_PER_WORKER * (NUM_WORKERS - 1);
+    while SUM.load(Ordering::Relaxed) != expected {
+        sched_yield();
+    }
+    println!("Workers added up to {}.", expected);
+
+    // The last worker exits, which does not affect the others.
+    let mut exit_code = 0;
+    let last = NUM_WORKERS - 1;
+    post_job(last, STOP);
+    assert_eq!(waitpid(tids[last], &mut exit_code), tids[last] as isize);
+    assert_eq!(exit_code, JOBS_PER_WORKER as i32 + 1);
+    assert!(!senders[last].send()); // the receiver is gone
+    for (i, &tid) in tids.iter().take(last).enumerate() {
+        post_job(i, STOP);
+        assert_eq!(waitpid(tid, &mut exit_code), tid as isize);
+    }
+    println!("uintr_threads passed!");
+    0
+}
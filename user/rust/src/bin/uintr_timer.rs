@@ -0,0 +1,94 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{
+    exit, fork, get_time, sleep, uintr_set_timer, waitpid, UintrReceiver, UINTR_TIMER_ONESHOT,
+};
+
+const ONESHOT_VECTOR: usize = 0;
+const PERIODIC_VECTOR: usize = 1;
+const DELAY_MS: usize = 20;
+const PERIOD_MS: usize = 10;
+const PERIODS: usize = 5;
+
+static HANDLED: [AtomicUsize; 2] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+
+extern "C" fn uintr_handler(vector: u64) {
+    HANDLED[vector as usize].fetch_add(1, Ordering::Relaxed);
+}
+
+fn handled(vector: usize) -> usize {
+    HANDLED[vector].load(Ordering::Relaxed)
+}
+
+fn ms_to_ns(ms: usize) -> usize {
+    ms * 1_000_000
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let receiver = UintrReceiver::register(uintr_handler).unwrap();
+    let oneshot_fd = receiver.vector_fd(ONESHOT_VECTOR).unwrap();
+    let periodic_fd = receiver.vector_fd(PERIODIC_VECTOR).unwrap();
+    let bad_flags = UINTR_TIMER_ONESHOT << 1;
+    assert_eq!(
+        uintr_set_timer(oneshot_fd.as_raw(), ms_to_ns(DELAY_MS), bad_flags),
+        -1
+    );
+
+    // Only the receiver can set timers of its vectors.
+    let pid = fork();
+    if pid == 0 {
+        assert!(!receiver.set_timer(&periodic_fd, ms_to_ns(PERIOD_MS)));
+        exit(0);
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+
+    // A one-shot timer expires once, after the delay.
+    let start = get_time();
+    assert!(receiver.set_oneshot_timer(&oneshot_fd, ms_to_ns(DELAY_MS)));
+    while handled(ONESHOT_VECTOR) == 0 {
+        receiver.wait();
+    }
+    let elapsed = get_time() - start;
+    assert!(elapsed >= DELAY_MS as isize);
+    println!("One-shot timer expired after {} ms.", elapsed);
+    assert_eq!(sleep(DELAY_MS * 3), 0);
+    assert_eq!(handled(ONESHOT_VECTOR), 1);
+
+    // A periodic timer expires until it is disarmed.
+    let start = get_time();
+    assert!(receiver.set_timer(&periodic_fd, ms_to_ns(PERIOD_MS)));
+    while handled(PERIODIC_VECTOR) < PERIODS {
+        receiver.wait();
+    }
+    let elapsed = get_time() - start;
+    assert!(elapsed >= (PERIOD_MS * PERIODS) as isize);
+    println!(
+        "Periodic timer expired {} times in {} ms.",
+        PERIODS, elapsed
+    );
+    assert!(receiver.set_timer(&periodic_fd, 0));
+    let n = handled(PERIODIC_VECTOR);
+    assert_eq!(sleep(PERIOD_MS * 3), 0);
+    assert_eq!(handled(PERIODIC_VECTOR), n);
+
+    // A receiver has only one timer, which is replaced.
+    assert!(receiver.set_timer(&periodic_fd, ms_to_ns(PERIOD_MS)));
+    assert!(receiver.set_oneshot_timer(&oneshot_fd, ms_to_ns(PERIOD_MS)));
+    let n = handled(PERIODIC_VECTOR);
+    while handled(ONESHOT_VECTOR) == 1 {
+        receiver.wait();
+    }
+    assert_eq!(sleep(PERIOD_MS * 3), 0);
+    assert_eq!(handled(PERIODIC_VECTOR), n);
+    assert_eq!(handled(ONESHOT_VECTOR), 2);
+    println!("uintr_timer passed!");
+    0
+}
@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{read, UintrReceiver};
+
+/// IRQ of the UART receiving the console input.
+#[cfg(target_arch = "x86_64")]
+const UART_IRQ: usize = 4; // COM1
+#[cfg(target_arch = "aarch64")]
+const UART_IRQ: usize = 33; // PL011
+
+const STDIN: usize = 0;
+
+static IRQS: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn uintr_handler(vector: u64) {
+    assert_eq!(vector, 0);
+    IRQS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// A user-space driver of the UART RX interrupt, which is forwarded by the
+/// kernel as a user interrupt. It needs input, so it is not run by
+/// `usertests`.
+#[no_mangle]
+pub fn main() -> i32 {
+    let receiver = UintrReceiver::register(uintr_handler).unwrap();
+    let uintr_fd = receiver.vector_fd(0).unwrap();
+    assert!(!receiver.ack_irq(UART_IRQ)); // not forwarded yet
+    assert!(receiver.register_irq(UART_IRQ, &uintr_fd));
+    assert!(!receiver.register_irq(UART_IRQ, &uintr_fd));
+
+    println!("Type characters one by one, or 'q' to quit:");
+    let mut handled = 0;
+    loop {
+        while IRQS.load(Ordering::Relaxed) == handled {
+            receiver.wait();
+        }
+        handled += 1;
+        let mut c = [0u8; 1];
+        assert_eq!(read(STDIN, &mut c), 1);
+        println!("UART IRQ #{}: received {:?}", handled, c[0] as char);
+        if c[0] == b'q' {
+            break;
+        }
+        // the IRQ is masked until acknowledged
+        assert!(receiver.ack_irq(UART_IRQ));
+    }
+    println!("uintr_uart passed!");
+    0
+}
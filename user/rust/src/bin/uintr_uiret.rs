@@ -0,0 +1,86 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{
+    exit, fork, sched_yield, shmat, shmget, waitpid, UintrReceiver, UintrSender, EINTR, IPC_PRIVATE,
+};
+
+const ITERATIONS: u64 = 200_000;
+const INTERRUPTS: usize = 100;
+
+static SEED: u64 = 0x2545_f491_4f6c_dd1d;
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn uintr_handler(vector: u64) {
+    assert_eq!(vector, 0);
+    HANDLED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Keeps many values live in registers (and flags) for a long time, so that
+/// any register not restored by `uiret` would corrupt the result.
+#[inline(never)]
+fn compute(seed: u64) -> u64 {
+    let (mut a, mut b, mut c, mut d) = (seed, !seed, seed.rotate_left(17), seed ^ 0x5555);
+    for i in 0..ITERATIONS {
+        a = a
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        b ^= a.rotate_left(13);
+        c = c.wrapping_add(b ^ i);
+        d = if c > a {
+            d.rotate_right(7) ^ c
+        } else {
+            d.wrapping_sub(b)
+        };
+    }
+    a ^ b ^ c ^ d
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let receiver = UintrReceiver::register(uintr_handler).unwrap();
+    let uintr_fd = receiver.vector_fd(0).unwrap();
+    let shmid = shmget(IPC_PRIVATE, 4096, 0);
+    let done = unsafe { &*(shmat(shmid, 0, 0) as *const AtomicUsize) };
+
+    let seed = unsafe { core::ptr::read_volatile(&SEED) };
+    let expected = compute(seed);
+
+    let pid = fork();
+    if pid == 0 {
+        let sender = UintrSender::register(&uintr_fd).unwrap();
+        let done = unsafe { &*(shmat(shmid, 0, 0) as *const AtomicUsize) };
+        while done.load(Ordering::Acquire) == 0 {
+            assert!(sender.send());
+            sched_yield();
+        }
+        exit(0);
+    }
+
+    let mut rounds = 0;
+    while HANDLED.load(Ordering::Relaxed) < INTERRUPTS {
+        assert_eq!(compute(seed), expected);
+        rounds += 1;
+    }
+    done.store(1, Ordering::Release);
+
+    let mut exit_code = 0;
+    // the sender may still send before it sees `done`
+    let mut ret = waitpid(pid as usize, &mut exit_code);
+    while ret == EINTR {
+        ret = waitpid(pid as usize, &mut exit_code);
+    }
+    assert_eq!(ret, pid);
+    assert_eq!(exit_code, 0);
+    println!(
+        "{} user interrupts handled in {} rounds of computation.",
+        HANDLED.load(Ordering::Relaxed),
+        rounds
+    );
+    println!("uintr_uiret passed!");
+    0
+}
@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+const SYSCALL_UINTR_UIRET: usize = 305;
+/// Neither a user address nor canonical on x86_64.
+const BAD_ADDR: u64 = 0xdead_0000_0000_0000;
+const FRAME_WORDS: usize = 64;
+
+/// Calls `uiret` with the user stack pointing to `frame`, which is read as
+/// the interrupted context.
+fn uiret_with_frame(frame: &[u64; FRAME_WORDS]) -> isize {
+    let ret;
+    unsafe {
+        #[cfg(target_arch = "x86_64")]
+        core::arch::asm!(
+            "mov r12, rsp",
+            "mov rsp, {frame}",
+            "syscall",
+            "mov rsp, r12",
+            frame = in(reg) frame.as_ptr(),
+            inlateout("rax") SYSCALL_UINTR_UIRET => ret,
+            out("rcx") _,
+            out("r11") _,
+            out("r12") _,
+        );
+        #[cfg(target_arch = "aarch64")]
+        core::arch::asm!(
+            "mov x9, sp",
+            "mov sp, {frame}",
+            "svc #0",
+            "mov sp, x9",
+            frame = in(reg) frame.as_ptr(),
+            in("x8") SYSCALL_UINTR_UIRET,
+            lateout("x0") ret,
+            out("x9") _,
+        );
+    }
+    ret
+}
+
+/// A frame returning to a kernel or non-canonical address is refused, instead
+/// of faulting in the kernel on the way back to user mode.
+#[no_mangle]
+pub fn main() -> i32 {
+    // every field, including the PC, SP, and the RCX/R11 that `sysretq`
+    // would use, is the bad address
+    let frame = [BAD_ADDR; FRAME_WORDS];
+    assert_eq!(uiret_with_frame(&frame), -1);
+    println!("uintr_uiret_frame passed!");
+    0
+}
@@ -0,0 +1,92 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use user_lib::{exit, fork, uintr_notice, waitpid, UintrFd, UintrReceiver, UintrSender};
+
+const TRIGGER_VECTOR: usize = 0;
+/// Sent in this order from the handler of `TRIGGER_VECTOR`.
+const VECTORS: [usize; 3] = [1, 63, 5];
+/// Delivered in this order, from the highest vector.
+const EXPECTED_ORDER: [usize; 4] = [0, 63, 5, 1];
+
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+static ORDER: [AtomicUsize; 4] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+/// UITT indices of `VECTORS` for the self-sends in the handler.
+static INDICES: [AtomicUsize; 3] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+static RECEIVED: AtomicU64 = AtomicU64::new(0);
+
+extern "C" fn uintr_handler(vector: u64) {
+    let vector = vector as usize;
+    RECEIVED.fetch_or(1 << vector, Ordering::Relaxed);
+    let n = HANDLED.fetch_add(1, Ordering::Relaxed);
+    if n < ORDER.len() {
+        ORDER[n].store(vector, Ordering::Relaxed);
+    }
+    if vector == TRIGGER_VECTOR && n == 0 {
+        // handlers are not nested, so these are all pending on return
+        for index in &INDICES {
+            assert_eq!(uintr_notice(index.load(Ordering::Relaxed)), 0);
+        }
+    }
+}
+
+fn fork_sender(uintr_fd: &UintrFd) -> isize {
+    let pid = fork();
+    if pid == 0 {
+        assert!(UintrSender::register(uintr_fd).unwrap().send());
+        exit(0);
+    }
+    pid
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let receiver = UintrReceiver::register(uintr_handler).unwrap();
+    let trigger_fd = receiver.vector_fd(TRIGGER_VECTOR).unwrap();
+    let fds = VECTORS.map(|vector| receiver.vector_fd(vector).unwrap());
+    let senders = [0, 1, 2].map(|i| UintrSender::register(&fds[i]).unwrap());
+    for (index, sender) in INDICES.iter().zip(&senders) {
+        index.store(sender.index(), Ordering::Relaxed);
+    }
+    assert!(receiver.vector_fd(VECTORS[0]).is_none()); // already has a fd
+    assert!(receiver.vector_fd(64).is_none());
+
+    // Pending vectors are delivered from the highest one.
+    assert!(UintrSender::register(&trigger_fd).unwrap().send());
+    assert_eq!(HANDLED.load(Ordering::Relaxed), EXPECTED_ORDER.len());
+    for (i, &vector) in EXPECTED_ORDER.iter().enumerate() {
+        assert_eq!(ORDER[i].load(Ordering::Relaxed), vector);
+    }
+    println!("Vectors delivered in order {:?}.", EXPECTED_ORDER);
+
+    // Senders are told apart by their vectors.
+    RECEIVED.store(0, Ordering::Relaxed);
+    let mut pids = [0; 3];
+    for (pid, fd) in pids.iter_mut().zip(&fds) {
+        *pid = fork_sender(fd);
+    }
+    let all = VECTORS.iter().fold(0, |bits, v| bits | 1 << v);
+    while RECEIVED.load(Ordering::Relaxed) != all {
+        receiver.wait();
+    }
+    for pid in pids {
+        let mut exit_code = 0;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+        assert_eq!(exit_code, 0);
+    }
+    println!("uintr_vectors passed!");
+    0
+}
@@ -0,0 +1,45 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{exit, fork, get_time, sleep, uintr_wait, waitpid, UintrReceiver, UintrSender};
+
+const SLEEP_MS: usize = 100;
+
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn uintr_handler(vector: u64) {
+    assert_eq!(vector, 0);
+    HANDLED.fetch_add(1, Ordering::Relaxed);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(uintr_wait(0), -1); // not a receiver yet
+    let receiver = UintrReceiver::register(uintr_handler).unwrap();
+    let uintr_fd = receiver.vector_fd(0).unwrap();
+
+    let pid = fork();
+    if pid == 0 {
+        let sender = UintrSender::register(&uintr_fd).unwrap();
+        sleep(SLEEP_MS);
+        assert!(sender.send());
+        exit(0);
+    }
+
+    let start = get_time();
+    receiver.wait();
+    let elapsed = get_time() - start;
+    // the handler has run before `uintr_wait` returns
+    assert_eq!(HANDLED.load(Ordering::Relaxed), 1);
+    println!("Receiver woken up by a user interrupt after {}ms.", elapsed);
+
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    println!("uintr_wait passed!");
+    0
+}
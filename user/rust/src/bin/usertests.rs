@@ -10,11 +10,31 @@ static TESTS: &[&str] = &[
     "forktest\0",
     "forktest2\0",
     "forktest_simple\0",
+    "green_threads\0",
     "hello_world\0",
     "matrix\0",
+    "shm_attach\0",
+    "shm_ctl\0",
+    "shm_detach\0",
+    "shm_fork\0",
+    "shm_key\0",
+    "shm_open\0",
     "sleep\0",
     "sleep_simple\0",
     "stack_overflow\0",
+    "uintr_channel\0",
+    "uintr_eintr\0",
+    "uintr_exec\0",
+    "uintr_exit\0",
+    "uintr_fork\0",
+    "uintr_grant\0",
+    "uintr_stats\0",
+    "uintr_threads\0",
+    "uintr_timer\0",
+    "uintr_uiret\0",
+    "uintr_uiret_frame\0",
+    "uintr_vectors\0",
+    "uintr_wait\0",
     "yield\0",
 ];
 
@@ -0,0 +1,191 @@
+//! Single-producer single-consumer channels between processes, made of a ring
+//! buffer in shared memory and a user interrupt as the doorbell.
+
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{
+    sched_yield, shmat, shmctl, shmdt, shmget, UintrFd, UintrReceiver, UintrSender, IPC_PRIVATE,
+    IPC_RMID,
+};
+
+/// Indices of the ring, in separate cache lines.
+#[repr(C, align(64))]
+struct RingIndex(AtomicUsize);
+
+#[repr(C)]
+struct RingHeader {
+    /// Index of the next element to receive, advanced by the consumer.
+    head: RingIndex,
+    /// Index of the next element to send, advanced by the producer.
+    tail: RingIndex,
+}
+
+/// A ring buffer of `T` in a shared memory segment, which is shared with the
+/// children forked after it is created.
+///
+/// The indices only increase, and wrap around the capacity when used. The
+/// segment is detached from the calling process on drop.
+pub struct ShmRing<T: Copy> {
+    header: &'static RingHeader,
+    slots: *mut T,
+    capacity: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Copy> ShmRing<T> {
+    /// Creates a ring of `capacity` elements in a new shared memory segment.
+    pub fn new(capacity: usize) -> Option<Self> {
+        if capacity == 0 {
+            return None;
+        }
+        let size = size_of::<RingHeader>() + capacity * size_of::<T>();
+        let shmid = shmget(IPC_PRIVATE, size, 0);
+        if shmid < 0 {
+            return None;
+        }
+        let base = shmat(shmid, 0, 0);
+        // Marked as removed at once, so that the segment is destroyed when
+        // the last process attached to it exits.
+        shmctl(shmid, IPC_RMID, None);
+        if base < 0 {
+            return None;
+        }
+        let header = unsafe { &*(base as *const RingHeader) };
+        header.head.0.store(0, Ordering::Relaxed);
+        header.tail.0.store(0, Ordering::Release);
+        Some(Self {
+            header,
+            slots: (base as usize + size_of::<RingHeader>()) as *mut T,
+            capacity,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        // `head` is read first, as it never passes `tail`. Both may advance in
+        // between when called by neither end, so the result is clamped.
+        let head = self.header.head.0.load(Ordering::Acquire);
+        let tail = self.header.tail.0.load(Ordering::Acquire);
+        (tail - head).min(self.capacity)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn base(&self) -> usize {
+        self.header as *const RingHeader as usize
+    }
+
+    /// Appends `value` by the producer, returns whether the ring was empty
+    /// before, or gives it back if the ring is full.
+    fn push(&self, value: T) -> Result<bool, T> {
+        let tail = self.header.tail.0.load(Ordering::Relaxed);
+        if tail - self.header.head.0.load(Ordering::Acquire) == self.capacity {
+            return Err(value);
+        }
+        unsafe { self.slots.add(tail % self.capacity).write_volatile(value) };
+        self.header.tail.0.store(tail + 1, Ordering::SeqCst);
+        // Checked after the element is published, so that either the
+        // consumer sees it before waiting, or it is rung.
+        Ok(self.header.head.0.load(Ordering::SeqCst) == tail)
+    }
+
+    /// Appends `value` by the producer, or gives it back if the ring is full.
+    pub fn try_push(&self, value: T) -> Result<(), T> {
+        self.push(value).map(|_| ())
+    }
+
+    /// Takes the oldest element by the consumer, if there is one.
+    pub fn try_pop(&self) -> Option<T> {
+        let head = self.header.head.0.load(Ordering::Relaxed);
+        if head == self.header.tail.0.load(Ordering::SeqCst) {
+            return None;
+        }
+        let value = unsafe { self.slots.add(head % self.capacity).read_volatile() };
+        self.header.head.0.store(head + 1, Ordering::SeqCst);
+        Some(value)
+    }
+}
+
+impl<T: Copy> Drop for ShmRing<T> {
+    fn drop(&mut self) {
+        shmdt(self.base());
+    }
+}
+
+/// The producer of a channel, which rings the doorbell of the consumer when
+/// the ring becomes non-empty.
+pub struct ChannelSender<'a, T: Copy> {
+    ring: &'a ShmRing<T>,
+    doorbell: UintrSender<'a>,
+}
+
+impl<'a, T: Copy> ChannelSender<'a, T> {
+    /// Registers the calling task as the producer of `ring`, whose consumer
+    /// is the receiver of `doorbell`.
+    pub fn new(ring: &'a ShmRing<T>, doorbell: &'a UintrFd) -> Option<Self> {
+        Some(Self {
+            ring,
+            doorbell: UintrSender::register(doorbell)?,
+        })
+    }
+
+    /// Sends `value`, or gives it back if the ring is full.
+    pub fn try_send(&self, value: T) -> Result<(), T> {
+        if self.ring.push(value)? {
+            self.doorbell.send();
+        }
+        Ok(())
+    }
+
+    /// Sends `value`, yielding the CPU while the ring is full.
+    pub fn send(&self, mut value: T) {
+        while let Err(v) = self.try_send(value) {
+            value = v;
+            sched_yield();
+        }
+    }
+}
+
+/// The consumer of a channel, which blocks until the doorbell is rung when
+/// the ring is empty.
+pub struct ChannelReceiver<'a, T: Copy> {
+    ring: &'a ShmRing<T>,
+    receiver: &'a UintrReceiver,
+}
+
+impl<'a, T: Copy> ChannelReceiver<'a, T> {
+    /// Makes the calling task the consumer of `ring`. The producer rings the
+    /// vector of `receiver` that it is given the file descriptor of, and the
+    /// handler is called for that as usual.
+    pub fn new(ring: &'a ShmRing<T>, receiver: &'a UintrReceiver) -> Self {
+        Self { ring, receiver }
+    }
+
+    pub fn try_recv(&self) -> Option<T> {
+        self.ring.try_pop()
+    }
+
+    /// Receives a value, blocking while the ring is empty.
+    pub fn recv(&self) -> T {
+        loop {
+            if let Some(value) = self.ring.try_pop() {
+                return value;
+            }
+            // The doorbell would be handled before waiting for it, if it were
+            // rung after the ring is checked.
+            self.receiver.disable();
+            if self.ring.is_empty() {
+                self.receiver.wait();
+            }
+            self.receiver.enable();
+        }
+    }
+}
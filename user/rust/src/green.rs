@@ -0,0 +1,199 @@
+//! M:1 green threads, which are preempted by a periodic user interrupt from
+//! the kernel timer.
+//!
+//! Threads are switched inside the user interrupt handler when preempted, and
+//! resumed from there by `uiret`. The handler is not reentered before that,
+//! so threads resumed elsewhere enable user interrupts by themselves.
+
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::arch::{context_switch, init_context, stui};
+use crate::UintrReceiver;
+
+const MAX_THREADS: usize = 16;
+const THREAD_STACK_SIZE: usize = 4096 * 4; // 16K
+/// Vector of the preemption ticks.
+const TICK_VECTOR: usize = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ThreadState {
+    Free,
+    Ready,
+    Running,
+    Finished,
+}
+
+#[derive(Clone, Copy)]
+struct Thread {
+    state: ThreadState,
+    /// Saved stack pointer when not running.
+    sp: usize,
+    entry: Option<fn(usize) -> i32>,
+    arg: usize,
+    exit_code: i32,
+}
+
+impl Thread {
+    const FREE: Self = Self {
+        state: ThreadState::Free,
+        sp: 0,
+        entry: None,
+        arg: 0,
+        exit_code: 0,
+    };
+}
+
+/// Thread 0 is the main thread on the process stack, the others use
+/// `THREAD_STACKS[tid - 1]`.
+static mut THREADS: [Thread; MAX_THREADS] = [Thread::FREE; MAX_THREADS];
+static mut THREAD_STACKS: [[u8; THREAD_STACK_SIZE]; MAX_THREADS - 1] =
+    [[0; THREAD_STACK_SIZE]; MAX_THREADS - 1];
+static mut CURRENT: usize = 0;
+
+/// Set while the threads are being modified or switched, when the preemption
+/// ticks are ignored.
+static IN_SCHED: AtomicBool = AtomicBool::new(false);
+static PREEMPTIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Locks the scheduler, fails if it is already locked by the interrupted
+/// thread.
+fn try_lock_sched() -> bool {
+    !IN_SCHED.swap(true, Ordering::Acquire)
+}
+
+/// Locks the scheduler from a running thread, which never holds it.
+fn lock_sched() {
+    assert!(try_lock_sched());
+}
+
+fn unlock_sched() {
+    IN_SCHED.store(false, Ordering::Release);
+}
+
+extern "C" fn tick_handler(vector: u64) {
+    assert_eq!(vector, TICK_VECTOR as u64);
+    if try_lock_sched() {
+        PREEMPTIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { switch_to_next() };
+    }
+}
+
+/// Switches to the next ready thread in round-robin order, if there is one.
+/// The scheduler must be locked, and is unlocked when the current thread is
+/// resumed.
+unsafe fn switch_to_next() {
+    let prev = CURRENT;
+    let next = (1..=MAX_THREADS)
+        .map(|i| (prev + i) % MAX_THREADS)
+        .find(|&tid| THREADS[tid].state == ThreadState::Ready);
+    match next {
+        Some(next) if next != prev => {
+            if THREADS[prev].state == ThreadState::Running {
+                THREADS[prev].state = ThreadState::Ready;
+            }
+            THREADS[next].state = ThreadState::Running;
+            CURRENT = next;
+            context_switch(&mut THREADS[prev].sp, THREADS[next].sp);
+        }
+        _ => assert_eq!(THREADS[prev].state, ThreadState::Running),
+    }
+    unlock_sched();
+}
+
+extern "C" fn thread_entry() -> ! {
+    // switched to from the scheduler, maybe in the handler
+    unlock_sched();
+    stui();
+    let (entry, arg) = unsafe { (THREADS[CURRENT].entry.unwrap(), THREADS[CURRENT].arg) };
+    let exit_code = entry(arg);
+    lock_sched();
+    unsafe {
+        THREADS[CURRENT].state = ThreadState::Finished;
+        THREADS[CURRENT].exit_code = exit_code;
+        switch_to_next();
+    }
+    unreachable!("finished green thread resumed");
+}
+
+/// Starts preempting the green threads every `interval_ms`, with the calling
+/// task registered as a user interrupt receiver for good. Fails if it is
+/// already a receiver.
+pub fn init(interval_ms: usize) -> bool {
+    let receiver = match UintrReceiver::register(tick_handler) {
+        Some(receiver) => receiver,
+        None => return false,
+    };
+    let tick_fd = receiver.vector_fd(TICK_VECTOR).unwrap();
+    unsafe { THREADS[0].state = ThreadState::Running };
+    if !receiver.set_timer(&tick_fd, interval_ms * 1_000_000) {
+        return false;
+    }
+    // the timer keeps the vector alive
+    core::mem::forget(receiver);
+    true
+}
+
+/// Spawns a green thread running `entry(arg)`, returns its thread ID.
+pub fn spawn(entry: fn(usize) -> i32, arg: usize) -> Option<usize> {
+    lock_sched();
+    let tid = unsafe { (1..MAX_THREADS).find(|&tid| THREADS[tid].state == ThreadState::Free) };
+    if let Some(tid) = tid {
+        unsafe {
+            let stack_top = THREAD_STACKS[tid - 1].as_ptr_range().end as usize;
+            THREADS[tid] = Thread {
+                state: ThreadState::Ready,
+                sp: init_context(stack_top, thread_entry),
+                entry: Some(entry),
+                arg,
+                exit_code: 0,
+            };
+        }
+    }
+    unlock_sched();
+    tid
+}
+
+/// Gives up the CPU to the next ready green thread.
+pub fn yield_now() {
+    lock_sched();
+    unsafe { switch_to_next() };
+    // may be resumed by a thread in the handler
+    stui();
+}
+
+/// Waits for the green thread `tid` to finish, returns its exit code. Fails if
+/// there is no such thread.
+pub fn join(tid: usize) -> Option<i32> {
+    loop {
+        lock_sched();
+        let state = unsafe { THREADS.get(tid).map(|t| t.state) };
+        match state {
+            Some(ThreadState::Finished) => {
+                let exit_code = unsafe {
+                    THREADS[tid].state = ThreadState::Free;
+                    THREADS[tid].exit_code
+                };
+                unlock_sched();
+                return Some(exit_code);
+            }
+            Some(ThreadState::Ready) => {
+                unlock_sched();
+                yield_now();
+            }
+            _ => {
+                unlock_sched();
+                return None;
+            }
+        }
+    }
+}
+
+/// Returns the ID of the running green thread.
+pub fn current() -> usize {
+    unsafe { CURRENT }
+}
+
+/// Returns how many times the green threads have been preempted.
+pub fn preemptions() -> usize {
+    PREEMPTIONS.load(Ordering::Relaxed)
+}
@@ -1,6 +1,7 @@
 #![no_std]
 #![feature(linkage)]
 #![feature(asm_const)]
+#![feature(asm_sym)]
 #![feature(naked_functions)]
 #![feature(panic_info_message)]
 
@@ -8,8 +9,14 @@
 pub mod console;
 
 mod arch;
+mod channel;
+pub mod green;
 mod lang_items;
 mod syscall;
+mod uintr;
+
+pub use channel::{ChannelReceiver, ChannelSender, ShmRing};
+pub use uintr::{UintrFd, UintrGrant, UintrHandler, UintrReceiver, UintrSender, UintrStats};
 
 #[repr(C)]
 pub struct TimeSpec {
@@ -41,6 +48,10 @@ pub fn write(fd: usize, buf: &[u8]) -> isize {
     sys_write(fd, buf)
 }
 
+pub fn close(fd: usize) -> isize {
+    sys_close(fd)
+}
+
 pub fn exit(exit_code: i32) -> ! {
     sys_exit(exit_code)
 }
@@ -71,7 +82,7 @@ pub fn wait(exit_code: &mut i32) -> isize {
             -2 => {
                 sched_yield();
             }
-            // -1 or a real pid
+            // -1, EINTR or a real pid
             exit_pid => return exit_pid,
         }
     }
@@ -83,17 +94,24 @@ pub fn waitpid(pid: usize, exit_code: &mut i32) -> isize {
             -2 => {
                 sched_yield();
             }
-            // -1 or a real pid
+            // -1, EINTR or a real pid
             exit_pid => return exit_pid,
         }
     }
 }
 
-pub fn sleep(period_ms: usize) {
+/// Returned by `sleep`, `waitpid` and `read` if they are interrupted by a
+/// user interrupt, after the handler has run.
+pub const EINTR: isize = -4;
+/// Returned by `ftruncate` if it would shrink a shared memory object below one
+/// of its mappings.
+pub const EBUSY: isize = -16;
+
+pub fn sleep(period_ms: usize) -> isize {
     sys_nanosleep(&TimeSpec {
         sec: period_ms / 1000,
         nsec: (period_ms % 1000) * 1_000_000,
-    });
+    })
 }
 
 pub fn thread_spawn(entry: fn(usize) -> i32, arg: usize) -> usize {
@@ -110,6 +128,38 @@ pub fn thread_spawn(entry: fn(usize) -> i32, arg: usize) -> usize {
 }
 
 pub const IPC_PRIVATE: usize = 0;
+/// Creates the segment of the key if it does not exist.
+pub const IPC_CREAT: usize = 0o1000;
+/// Fails if the segment of the key exists, with `IPC_CREAT`.
+pub const IPC_EXCL: usize = 0o2000;
+/// Removes the segment after the last detach.
+pub const IPC_RMID: usize = 0;
+/// Gets the status of the segment.
+pub const IPC_STAT: usize = 2;
+/// Attaches the segment read-only.
+pub const SHM_RDONLY: usize = 0o10000;
+/// Rounds the attach address down to a multiple of `SHMLBA`.
+pub const SHM_RND: usize = 0o20000;
+/// Alignment of the attach address.
+pub const SHMLBA: usize = 4096;
+
+/// Status of a shared memory segment returned by `shmctl(IPC_STAT)`.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ShmidDs {
+    /// Key given to `shmget`.
+    pub shm_key: usize,
+    /// Size of the segment in bytes.
+    pub shm_segsz: usize,
+    /// PID of the creator.
+    pub shm_cpid: usize,
+    /// Number of current attaches.
+    pub shm_nattch: usize,
+    /// Time of the last attach, in milliseconds.
+    pub shm_atime: usize,
+    /// Time of the last detach, in milliseconds.
+    pub shm_dtime: usize,
+}
 
 pub fn shmget(key: usize, size: usize, oflag: usize) -> isize {
     sys_shmget(key, size, oflag)
@@ -119,16 +169,81 @@ pub fn shmat(shmid: isize, shmaddr: usize, flag: usize) -> isize {
     sys_shmat(shmid as usize, shmaddr, flag)
 }
 
-pub fn uintr_register_receiver(handler: usize) -> isize {
-    sys_uintr_register_receiver(handler)
+pub fn shmdt(shmaddr: usize) -> isize {
+    sys_shmdt(shmaddr)
+}
+
+/// Controls the segment `shmid` with `cmd`. `buf` is written for `IPC_STAT`,
+/// and can be `None` otherwise.
+pub fn shmctl(shmid: isize, cmd: usize, buf: Option<&mut ShmidDs>) -> isize {
+    let buf = buf.map_or(0, |ds| ds as *mut _ as usize);
+    sys_shmctl(shmid as usize, cmd, buf)
+}
+
+/// Opens the object read-only.
+pub const O_RDONLY: usize = 0;
+/// Opens the object read-write.
+pub const O_RDWR: usize = 0o2;
+/// Creates the object if it does not exist.
+pub const O_CREAT: usize = 0o100;
+/// Fails if the object exists, with `O_CREAT`.
+pub const O_EXCL: usize = 0o200;
+/// Truncates the object to 0 bytes.
+pub const O_TRUNC: usize = 0o1000;
+
+pub const PROT_READ: usize = 0x1;
+pub const PROT_WRITE: usize = 0x2;
+pub const PROT_EXEC: usize = 0x4;
+/// Shares the mapping with other processes mapping the object.
+pub const MAP_SHARED: usize = 0x01;
+/// Maps at exactly the given address, which must be free.
+pub const MAP_FIXED: usize = 0x10;
+
+/// Opens the shared memory object `name`, returns its file descriptor. `name`
+/// starts with `/`, and ends with `\0`.
+pub fn shm_open(name: &str, oflag: usize, mode: usize) -> isize {
+    sys_shm_open(name, oflag, mode)
+}
+
+/// Removes the shared memory object `name`, which ends with `\0`.
+pub fn shm_unlink(name: &str) -> isize {
+    sys_shm_unlink(name)
+}
+
+/// Resizes the shared memory object `fd`, which may grow while it is mapped.
+/// Returns `EBUSY` if the pages beyond `length` are still mapped.
+pub fn ftruncate(fd: usize, length: usize) -> isize {
+    sys_ftruncate(fd, length)
+}
+
+/// Maps the shared memory object `fd`, returns the address of the mapping.
+pub fn mmap(addr: usize, len: usize, prot: usize, flags: usize, fd: usize, offset: usize) -> isize {
+    sys_mmap(addr, len, prot, flags, fd, offset)
+}
+
+/// Unmaps the whole mapping made by `mmap` at `addr` with `len`.
+pub fn munmap(addr: usize, len: usize) -> isize {
+    sys_munmap(addr, len)
+}
+
+pub fn uintr_register_handler(handler: usize, flags: usize) -> isize {
+    sys_uintr_register_handler(handler, flags)
 }
 
-pub fn uintr_register_link(vector: usize, shmem_id: &usize) -> isize {
-    sys_uintr_register_link(vector, shmem_id)
+pub fn uintr_unregister_handler(flags: usize) -> isize {
+    sys_uintr_unregister_handler(flags)
 }
 
-pub fn uintr_register_sender(link_id: usize, shmem_id: &usize) -> isize {
-    sys_uintr_register_sender(link_id, shmem_id)
+pub fn uintr_vector_fd(vector: usize, flags: usize) -> isize {
+    sys_uintr_vector_fd(vector, flags)
+}
+
+pub fn uintr_register_sender(uintr_fd: usize, flags: usize) -> isize {
+    sys_uintr_register_sender(uintr_fd, flags)
+}
+
+pub fn uintr_unregister_sender(uintr_fd: usize, flags: usize) -> isize {
+    sys_uintr_unregister_sender(uintr_fd, flags)
 }
 
 pub fn uintr_notice(index: usize) -> isize {
@@ -138,3 +253,44 @@ pub fn uintr_notice(index: usize) -> isize {
 pub fn uintr_uiret() -> isize {
     sys_uintr_uiret()
 }
+
+pub fn uintr_wait(flags: usize) -> isize {
+    sys_uintr_wait(flags)
+}
+
+pub fn uintr_register_irq(irq: usize, uintr_fd: usize, flags: usize) -> isize {
+    sys_uintr_register_irq(irq, uintr_fd, flags)
+}
+
+pub fn uintr_irq_ack(irq: usize) -> isize {
+    sys_uintr_irq_ack(irq)
+}
+
+pub fn uintr_stats(pid: usize, stats: &mut UintrStats) -> isize {
+    sys_uintr_stats(pid, stats)
+}
+
+pub fn uintr_stui() -> isize {
+    sys_uintr_stui()
+}
+
+pub fn uintr_clui() -> isize {
+    sys_uintr_clui()
+}
+
+/// The timer set by `uintr_set_timer` expires only once.
+pub const UINTR_TIMER_ONESHOT: usize = 1 << 0;
+
+pub fn uintr_set_timer(uintr_fd: usize, delay_ns: usize, flags: usize) -> isize {
+    sys_uintr_set_timer(uintr_fd, delay_ns, flags)
+}
+
+/// Creates a revocable grant of the vector of `uintr_fd`, returns its file
+/// descriptor.
+pub fn uintr_grant(uintr_fd: usize, flags: usize) -> isize {
+    sys_uintr_grant(uintr_fd, flags)
+}
+
+pub fn uintr_revoke(grant_fd: usize, flags: usize) -> isize {
+    sys_uintr_revoke(grant_fd, flags)
+}
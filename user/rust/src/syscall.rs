@@ -1,10 +1,13 @@
-use super::TimeSpec;
-use crate::arch::syscall;
+use super::{TimeSpec, UintrStats};
+use crate::arch::{syscall, syscall6};
 
 pub use crate::arch::sys_clone;
 
 pub const SYSCALL_READ: usize = 0;
 pub const SYSCALL_WRITE: usize = 1;
+pub const SYSCALL_CLOSE: usize = 3;
+pub const SYSCALL_MMAP: usize = 9;
+pub const SYSCALL_MUNMAP: usize = 11;
 pub const SYSCALL_YIELD: usize = 24;
 pub const SYSCALL_NANOSLEEP: usize = 35;
 pub const SYSCALL_GETPID: usize = 39;
@@ -13,16 +16,30 @@ pub const SYSCALL_FORK: usize = 57;
 pub const SYSCALL_EXEC: usize = 59;
 pub const SYSCALL_EXIT: usize = 60;
 pub const SYSCALL_WAITPID: usize = 61;
+pub const SYSCALL_FTRUNCATE: usize = 77;
 pub const SYSCALL_GET_TIME: usize = 96;
 pub const SYSCALL_SHMGET: usize = 233;
 pub const SYSCALL_SHMAT: usize = 234;
 pub const SYSCALL_SHMDT: usize = 235;
 pub const SYSCALL_SHMCTL: usize = 236;
-pub const SYSCALL_UINTR_REGISTER_RECEIVER: usize = 301;
-pub const SYSCALL_UINTR_REGISTER_LINK: usize = 302;
-pub const SYSCALL_UINTR_REGISTER_SENDER: usize = 303;
 pub const SYSCALL_UINTR_NOTICE: usize = 304;
 pub const SYSCALL_UINTR_UIRET: usize = 305;
+pub const SYSCALL_UINTR_REGISTER_IRQ: usize = 306;
+pub const SYSCALL_UINTR_IRQ_ACK: usize = 307;
+pub const SYSCALL_UINTR_STATS: usize = 308;
+pub const SYSCALL_UINTR_STUI: usize = 309;
+pub const SYSCALL_UINTR_SET_TIMER: usize = 310;
+pub const SYSCALL_UINTR_CLUI: usize = 311;
+pub const SYSCALL_UINTR_GRANT: usize = 312;
+pub const SYSCALL_UINTR_REVOKE: usize = 313;
+pub const SYSCALL_UINTR_REGISTER_HANDLER: usize = 449;
+pub const SYSCALL_UINTR_UNREGISTER_HANDLER: usize = 450;
+pub const SYSCALL_UINTR_VECTOR_FD: usize = 451;
+pub const SYSCALL_UINTR_REGISTER_SENDER: usize = 452;
+pub const SYSCALL_UINTR_UNREGISTER_SENDER: usize = 453;
+pub const SYSCALL_UINTR_WAIT: usize = 454;
+pub const SYSCALL_SHM_OPEN: usize = 455;
+pub const SYSCALL_SHM_UNLINK: usize = 456;
 
 pub fn sys_read(fd: usize, buffer: &mut [u8]) -> isize {
     syscall(
@@ -35,6 +52,10 @@ pub fn sys_write(fd: usize, buffer: &[u8]) -> isize {
     syscall(SYSCALL_WRITE, [fd, buffer.as_ptr() as usize, buffer.len()])
 }
 
+pub fn sys_close(fd: usize) -> isize {
+    syscall(SYSCALL_CLOSE, [fd, 0, 0])
+}
+
 pub fn sys_exit(exit_code: i32) -> ! {
     syscall(SYSCALL_EXIT, [exit_code as usize, 0, 0]);
     panic!("sys_exit never returns!");
@@ -76,16 +97,39 @@ pub fn sys_shmat(shmid: usize, shmaddr: usize, shmflg: usize) -> isize {
     syscall(SYSCALL_SHMAT, [shmid, shmaddr, shmflg])
 }
 
-pub fn sys_uintr_register_receiver(handler: usize) -> isize {
-    syscall(SYSCALL_UINTR_REGISTER_RECEIVER, [handler, 0, 0])
+pub fn sys_shmdt(shmaddr: usize) -> isize {
+    syscall(SYSCALL_SHMDT, [shmaddr, 0, 0])
+}
+
+pub fn sys_shmctl(shmid: usize, cmd: usize, buf: usize) -> isize {
+    syscall(SYSCALL_SHMCTL, [shmid, cmd, buf])
+}
+
+pub fn sys_shm_open(name: &str, oflag: usize, mode: usize) -> isize {
+    syscall(SYSCALL_SHM_OPEN, [name.as_ptr() as usize, oflag, mode])
+}
+
+pub fn sys_shm_unlink(name: &str) -> isize {
+    syscall(SYSCALL_SHM_UNLINK, [name.as_ptr() as usize, 0, 0])
+}
+
+pub fn sys_ftruncate(fd: usize, length: usize) -> isize {
+    syscall(SYSCALL_FTRUNCATE, [fd, length, 0])
 }
 
-pub fn sys_uintr_register_link(vector: usize, shmem_id: &usize) -> isize {
-    syscall(SYSCALL_UINTR_REGISTER_LINK, [vector, shmem_id as *const _ as usize, 0])
+pub fn sys_mmap(
+    addr: usize,
+    len: usize,
+    prot: usize,
+    flags: usize,
+    fd: usize,
+    offset: usize,
+) -> isize {
+    syscall6(SYSCALL_MMAP, [addr, len, prot, flags, fd, offset])
 }
 
-pub fn sys_uintr_register_sender(link_id: usize, shmem_id: &usize) -> isize {
-    syscall(SYSCALL_UINTR_REGISTER_SENDER, [link_id, shmem_id as *const _ as usize, 0])
+pub fn sys_munmap(addr: usize, len: usize) -> isize {
+    syscall(SYSCALL_MUNMAP, [addr, len, 0])
 }
 
 pub fn sys_uintr_notice(index: usize) -> isize {
@@ -95,3 +139,59 @@ pub fn sys_uintr_notice(index: usize) -> isize {
 pub fn sys_uintr_uiret() -> isize {
     syscall(SYSCALL_UINTR_UIRET, [0, 0, 0])
 }
+
+pub fn sys_uintr_register_irq(irq: usize, fd: usize, flags: usize) -> isize {
+    syscall(SYSCALL_UINTR_REGISTER_IRQ, [irq, fd, flags])
+}
+
+pub fn sys_uintr_irq_ack(irq: usize) -> isize {
+    syscall(SYSCALL_UINTR_IRQ_ACK, [irq, 0, 0])
+}
+
+pub fn sys_uintr_stats(pid: usize, stats: &mut UintrStats) -> isize {
+    syscall(SYSCALL_UINTR_STATS, [pid, stats as *mut _ as usize, 0])
+}
+
+pub fn sys_uintr_stui() -> isize {
+    syscall(SYSCALL_UINTR_STUI, [0, 0, 0])
+}
+
+pub fn sys_uintr_clui() -> isize {
+    syscall(SYSCALL_UINTR_CLUI, [0, 0, 0])
+}
+
+pub fn sys_uintr_set_timer(fd: usize, delay_ns: usize, flags: usize) -> isize {
+    syscall(SYSCALL_UINTR_SET_TIMER, [fd, delay_ns, flags])
+}
+
+pub fn sys_uintr_grant(fd: usize, flags: usize) -> isize {
+    syscall(SYSCALL_UINTR_GRANT, [fd, flags, 0])
+}
+
+pub fn sys_uintr_revoke(grant_fd: usize, flags: usize) -> isize {
+    syscall(SYSCALL_UINTR_REVOKE, [grant_fd, flags, 0])
+}
+
+pub fn sys_uintr_register_handler(handler: usize, flags: usize) -> isize {
+    syscall(SYSCALL_UINTR_REGISTER_HANDLER, [handler, flags, 0])
+}
+
+pub fn sys_uintr_unregister_handler(flags: usize) -> isize {
+    syscall(SYSCALL_UINTR_UNREGISTER_HANDLER, [flags, 0, 0])
+}
+
+pub fn sys_uintr_vector_fd(vector: usize, flags: usize) -> isize {
+    syscall(SYSCALL_UINTR_VECTOR_FD, [vector, flags, 0])
+}
+
+pub fn sys_uintr_register_sender(fd: usize, flags: usize) -> isize {
+    syscall(SYSCALL_UINTR_REGISTER_SENDER, [fd, flags, 0])
+}
+
+pub fn sys_uintr_unregister_sender(fd: usize, flags: usize) -> isize {
+    syscall(SYSCALL_UINTR_UNREGISTER_SENDER, [fd, flags, 0])
+}
+
+pub fn sys_uintr_wait(flags: usize) -> isize {
+    syscall(SYSCALL_UINTR_WAIT, [flags, 0, 0])
+}
@@ -0,0 +1,226 @@
+//! Typed handles of user interrupts.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::arch::{clui, stui, uintr_trampoline};
+use crate::syscall::*;
+use crate::UINTR_TIMER_ONESHOT;
+
+/// A user interrupt handler, which is called with the vector.
+pub type UintrHandler = extern "C" fn(vector: u64);
+
+/// The handler called by `uintr_trampoline`, shared by all the receivers of
+/// a process.
+pub(crate) static UINTR_HANDLER: AtomicUsize = AtomicUsize::new(0);
+
+/// The calling task registered as a user interrupt receiver. It is
+/// unregistered on drop.
+pub struct UintrReceiver {
+    _private: (),
+}
+
+impl UintrReceiver {
+    /// Registers the calling task as a receiver with `handler`, fails if it
+    /// is already a receiver.
+    pub fn register(handler: UintrHandler) -> Option<Self> {
+        if sys_uintr_register_handler(uintr_trampoline as usize, 0) != 0 {
+            return None;
+        }
+        // no user interrupts can be sent before there is a uintr file
+        // descriptor
+        UINTR_HANDLER.store(handler as usize, Ordering::Release);
+        Some(Self { _private: () })
+    }
+
+    /// Creates a uintr file descriptor for `vector`, which senders can be
+    /// registered with. Fails if `vector` already has one.
+    pub fn vector_fd(&self, vector: usize) -> Option<UintrFd> {
+        match sys_uintr_vector_fd(vector, 0) {
+            fd if fd >= 0 => Some(UintrFd(fd as usize)),
+            _ => None,
+        }
+    }
+
+    /// Blocks until a user interrupt is pending, which has been handled when
+    /// it returns, unless user interrupts are disabled.
+    pub fn wait(&self) {
+        assert_eq!(sys_uintr_wait(0), 0);
+    }
+
+    /// Forwards the device interrupt `irq` to the vector of `fd`. The IRQ is
+    /// masked after each interrupt until [`ack_irq`](Self::ack_irq).
+    pub fn register_irq(&self, irq: usize, fd: &UintrFd) -> bool {
+        sys_uintr_register_irq(irq, fd.0, 0) == 0
+    }
+
+    /// Unmasks the device interrupt `irq` after handling it.
+    pub fn ack_irq(&self, irq: usize) -> bool {
+        sys_uintr_irq_ack(irq) == 0
+    }
+
+    /// Sends a user interrupt to the vector of `fd` every `interval_ns` from
+    /// the kernel timer, or stops it if `interval_ns` is 0. It replaces the
+    /// timer set before, since a receiver has only one.
+    pub fn set_timer(&self, fd: &UintrFd, interval_ns: usize) -> bool {
+        sys_uintr_set_timer(fd.0, interval_ns, 0) == 0
+    }
+
+    /// Sends a user interrupt to the vector of `fd` once after `delay_ns`,
+    /// replacing the timer set before.
+    pub fn set_oneshot_timer(&self, fd: &UintrFd, delay_ns: usize) -> bool {
+        sys_uintr_set_timer(fd.0, delay_ns, UINTR_TIMER_ONESHOT) == 0
+    }
+
+    /// Creates a grant of the vector of `fd`, which can be revoked. Senders
+    /// registered with the grant, in the forked children it is passed to, can
+    /// not send user interrupts after it is revoked.
+    ///
+    /// Senders registered with `fd` itself are not affected by revocations, so
+    /// only grants should be passed to senders whose access may be withdrawn.
+    /// The user interrupts posted before a revocation are still delivered.
+    pub fn grant(&self, fd: &UintrFd) -> Option<UintrGrant> {
+        match sys_uintr_grant(fd.0, 0) {
+            grant_fd if grant_fd >= 0 => Some(UintrGrant(UintrFd(grant_fd as usize))),
+            _ => None,
+        }
+    }
+
+    /// Revokes `grant`, fails if it has been revoked already.
+    pub fn revoke(&self, grant: &UintrGrant) -> bool {
+        sys_uintr_revoke(grant.as_raw(), 0) == 0
+    }
+
+    /// Enables user interrupts inside the handler, which are disabled until
+    /// it returns otherwise.
+    pub fn enable(&self) {
+        stui();
+    }
+
+    /// Disables user interrupts. The ones sent meanwhile stay pending, and
+    /// can be waited for with [`wait`](Self::wait) without being handled.
+    pub fn disable(&self) {
+        clui();
+    }
+}
+
+impl Drop for UintrReceiver {
+    fn drop(&mut self) {
+        sys_uintr_unregister_handler(0);
+    }
+}
+
+/// A uintr file descriptor, which refers to a vector of a receiver. It is
+/// inherited by forked children, and closed on drop.
+pub struct UintrFd(usize);
+
+impl UintrFd {
+    /// Takes the ownership of the uintr file descriptor `fd`, which may have
+    /// been created by another thread.
+    pub fn from_raw(fd: usize) -> Self {
+        Self(fd)
+    }
+
+    pub fn as_raw(&self) -> usize {
+        self.0
+    }
+}
+
+impl Drop for UintrFd {
+    fn drop(&mut self) {
+        sys_close(self.0);
+    }
+}
+
+/// A revocable grant of a vector, which is a file descriptor as well. It is
+/// inherited by forked children, and closed on drop.
+pub struct UintrGrant(UintrFd);
+
+impl UintrGrant {
+    pub fn as_raw(&self) -> usize {
+        self.0.as_raw()
+    }
+}
+
+/// The calling task registered as a sender of a uintr file descriptor. It is
+/// unregistered on drop.
+pub struct UintrSender<'a> {
+    fd: &'a UintrFd,
+    index: usize,
+}
+
+impl<'a> UintrSender<'a> {
+    /// Registers the calling task as a sender of `fd`, fails if it is
+    /// already.
+    pub fn register(fd: &'a UintrFd) -> Option<Self> {
+        match sys_uintr_register_sender(fd.0, 0) {
+            index if index >= 0 => Some(Self {
+                fd,
+                index: index as usize,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Registers the calling task as a sender with `grant`, fails if it is
+    /// already a sender of the vector, or if the grant has been revoked.
+    pub fn register_grant(grant: &'a UintrGrant) -> Option<Self> {
+        Self::register(&grant.0)
+    }
+
+    /// Returns the index of the sender in the UITT.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Sends a user interrupt, fails if the receiver has gone, or if the
+    /// grant has been revoked.
+    pub fn send(&self) -> bool {
+        sys_uintr_notice(self.index) == 0
+    }
+}
+
+impl Drop for UintrSender<'_> {
+    fn drop(&mut self) {
+        sys_uintr_unregister_sender(self.fd.0, 0);
+    }
+}
+
+/// User interrupt statistics of a task.
+///
+/// Only the user interrupts going through the kernel are counted, so those
+/// sent and delivered by the CPU are not.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UintrStats {
+    /// Notices sent by the task as a sender.
+    pub sent: u64,
+    /// User interrupts posted to the task as a receiver.
+    pub posted: u64,
+    /// User interrupts delivered to the handler of the task.
+    pub delivered: u64,
+    /// Posts of vectors that were already pending.
+    pub coalesced: u64,
+    /// Total time from the posts to the handler entries, in nanoseconds.
+    pub total_latency_ns: u64,
+    /// Maximum time from a post to the handler entry, in nanoseconds.
+    pub max_latency_ns: u64,
+}
+
+impl UintrStats {
+    /// Gets the statistics of the task `pid`, or of the calling task if `pid`
+    /// is 0. Fails if there is no such task.
+    pub fn get(pid: usize) -> Option<Self> {
+        let mut stats = Self::default();
+        match sys_uintr_stats(pid, &mut stats) {
+            0 => Some(stats),
+            _ => None,
+        }
+    }
+
+    /// Average time from a post to the handler entry, in nanoseconds.
+    pub fn avg_latency_ns(&self) -> u64 {
+        self.total_latency_ns
+            .checked_div(self.delivered)
+            .unwrap_or(0)
+    }
+}